@@ -10,56 +10,75 @@ use crate::{
     error::Error,
     id::{Proof, PublicId},
     network_event::NetworkEvent,
-    observation::Observation,
+    observation::{Observation, ObservationHash, ObservationId},
+    serialise,
     vote::Vote,
     DkgResult, DkgResultWrapper,
 };
 use std::{
+    cmp::Ordering,
     collections::{vec_deque, BTreeMap, BTreeSet, VecDeque},
+    mem,
     ops::{Deref, DerefMut},
 };
 
 /// A struct representing a collection of votes by peers for an `Observation`.
 #[serde(bound = "")]
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Block<T: NetworkEvent, P: PublicId> {
     payload: Observation<T, P>,
     proofs: BTreeSet<Proof<P>>,
+    // Not covered by the proofs, so neither serialised nor taken into account in comparisons.
+    #[serde(skip)]
+    index: u64,
 }
 
 impl<T: NetworkEvent, P: PublicId> Block<T, P> {
     /// Create a `Block` with no signatures for a single DkgResult
     pub fn new_dkg_block((participants, dkg_result): (BTreeSet<P>, DkgResult)) -> Self {
+        let payload = Observation::DkgResult {
+            participants,
+            dkg_result: DkgResultWrapper(dkg_result),
+        };
         Self {
-            payload: Observation::DkgResult {
-                participants,
-                dkg_result: DkgResultWrapper(dkg_result),
-            },
+            payload,
             proofs: BTreeSet::new(),
+            index: 0,
         }
     }
 
-    /// Creates a `Block` from `votes`.
+    /// Creates a `Block` from `votes`. The votes' payloads must all be the same, or at least have
+    /// the same canonical hash (see `NetworkEvent::canonical_hash`), in which case the block's
+    /// payload is the one with the smallest serialised form. The proofs of all the votes verify
+    /// against it, as votes for such payloads sign over their canonical hash.
     pub fn new(votes: &BTreeMap<P, Vote<T, P>>) -> Result<Self, Error> {
-        let payload = if let Some(vote) = votes.values().next() {
-            vote.payload().clone()
+        let payload = if let Some(payload) = votes
+            .values()
+            .map(Vote::payload)
+            .min_by_key(|payload| serialise(*payload))
+        {
+            payload.clone()
         } else {
             return Err(Error::MissingVotes);
         };
+        let payload_hash = ObservationHash::from(&payload);
 
-        let proofs: Result<BTreeSet<_>, _> = votes
+        let proofs = votes
             .iter()
             .map(|(public_id, vote)| {
-                if *vote.payload() == payload {
+                if ObservationHash::from(vote.payload()) == payload_hash {
                     vote.create_proof(public_id)
                 } else {
                     Err(Error::MismatchedPayload)
                 }
             })
-            .collect();
-        let proofs = proofs?;
+            .collect::<Result<_, _>>()?;
 
-        Ok(Self { payload, proofs })
+        Ok(Self {
+            payload,
+            proofs,
+            index: 0,
+        })
     }
 
     /// Returns the payload of this block.
@@ -67,6 +86,31 @@ impl<T: NetworkEvent, P: PublicId> Block<T, P> {
         &self.payload
     }
 
+    /// Returns the ID of the payload of this block. It is the same as
+    /// [Parsec::observation_id](struct.Parsec.html#method.observation_id) of the payload, i.e. the
+    /// hash `Parsec` used to reach consensus on it. It is computed from the payload on each call.
+    pub fn payload_hash(&self) -> ObservationId {
+        ObservationId::new(ObservationHash::from(&self.payload))
+    }
+
+    /// Returns the position of this block's payload in the sequence of all the payloads consensused
+    /// since the section's genesis, which is the same on every node, including those which joined
+    /// later, as `Parsec::from_existing` replays the history from the genesis. Blocks are returned
+    /// by `Parsec::poll` in increasing order of index, but not with consecutive indices: payloads
+    /// internal to `Parsec`, such as DKG messages, are consensused without a block being returned.
+    /// A DKG result block has the index of the DKG message whose consensus completed it.
+    ///
+    /// The index isn't covered by the block's proofs, so it can't be verified by a third party. For
+    /// the same reason, it isn't serialised, and it's ignored when comparing blocks. Blocks not
+    /// returned by `Parsec::poll`, e.g. created via `Block::new` or deserialised, have index 0.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub(crate) fn set_index(&mut self, index: u64) {
+        self.index = index;
+    }
+
     /// Returns the proofs of this block.
     pub fn proofs(&self) -> &BTreeSet<Proof<P>> {
         &self.proofs
@@ -88,6 +132,33 @@ impl<T: NetworkEvent, P: PublicId> Block<T, P> {
         let proof = vote.create_proof(peer_id)?;
         Ok(self.proofs.insert(proof))
     }
+
+    // Approximate number of bytes held by this block.
+    pub(crate) fn estimated_memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.proofs.len() * mem::size_of::<Proof<P>>()
+    }
+}
+
+impl<T: NetworkEvent, P: PublicId> PartialEq for Block<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.payload == other.payload && self.proofs == other.proofs
+    }
+}
+
+impl<T: NetworkEvent, P: PublicId> Eq for Block<T, P> {}
+
+impl<T: NetworkEvent, P: PublicId> PartialOrd for Block<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: NetworkEvent, P: PublicId> Ord for Block<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.payload
+            .cmp(&other.payload)
+            .then_with(|| self.proofs.cmp(&other.proofs))
+    }
 }
 
 /// Group of blocks that were all created within the same meta-election.
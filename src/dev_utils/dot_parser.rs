@@ -205,8 +205,16 @@ fn parse_peers() -> Parser<u8, BTreeSet<PeerId>> {
 }
 
 fn parse_consensus_mode() -> Parser<u8, ConsensusMode> {
+    let threshold = (seq(b"Threshold { numerator: ") * parse_usize() - seq(b", denominator: ")
+        + parse_usize()
+        - seq(b" }"))
+    .map(|(numerator, denominator)| ConsensusMode::Threshold {
+        numerator,
+        denominator,
+    });
     let parser = seq(b"Single").map(|_| ConsensusMode::Single)
-        | seq(b"Supermajority").map(|_| ConsensusMode::Supermajority);
+        | seq(b"Supermajority").map(|_| ConsensusMode::Supermajority)
+        | threshold;
     let parser = comment_prefix() * seq(b"consensus_mode: ") * parser - next_line();
     parser
         .opt()
@@ -416,11 +424,34 @@ fn parse_last_ancestors() -> Parser<u8, BTreeMap<PeerId, usize>> {
     .map(|v| v.into_iter().collect())
 }
 
-fn parse_consensus_history() -> Parser<u8, Vec<ObservationKey>> {
-    let hash_line = comment_prefix()
+fn parse_observation_key_line() -> Parser<u8, ObservationKey> {
+    comment_prefix()
         * (parse_hash()).map(|hash| ObservationKey::Supermajority(ObservationHash(hash)))
-        - next_line();
-    comment_prefix() * seq(b"consensus_history:") * next_line() * hash_line.repeat(0..)
+        - next_line()
+}
+
+fn parse_consensus_history() -> Parser<u8, Vec<ObservationKey>> {
+    comment_prefix()
+        * seq(b"consensus_history:")
+        * next_line()
+        * parse_observation_key_line().repeat(0..)
+}
+
+fn parse_trimmed_history() -> Parser<u8, (usize, Vec<ObservationKey>)> {
+    let trimmed_history_len =
+        comment_prefix() * seq(b"trimmed_history_len: ") * parse_usize() - next_line();
+    let trimmed_payloads = comment_prefix()
+        * seq(b"trimmed_payloads:")
+        * next_line()
+        * parse_observation_key_line().repeat(0..);
+    trimmed_history_len + trimmed_payloads
+}
+
+fn parse_abandoned_payloads() -> Parser<u8, Vec<ObservationHash>> {
+    comment_prefix()
+        * seq(b"abandoned_payloads:")
+        * next_line()
+        * (comment_prefix() * parse_hash().map(ObservationHash) - next_line()).repeat(0..)
 }
 
 fn parse_hash() -> Parser<u8, Hash> {
@@ -447,6 +478,9 @@ struct ParsedMetaElection {
     observation_map: BTreeMap<ObservationKey, Observation<Transaction, PeerId>>,
     meta_events: BTreeMap<String, ParsedMetaEvent>,
     consensus_history: Vec<ObservationKey>,
+    trimmed_history_len: usize,
+    trimmed_payloads: Vec<ObservationKey>,
+    abandoned_payloads: Vec<ObservationHash>,
 }
 
 #[derive(Debug)]
@@ -459,7 +493,10 @@ struct ParsedMetaEvent {
 fn parse_meta_election(ctx: &Rc<ParserCtx>) -> Parser<u8, ParsedMetaElection> {
     seq(b"/// ===== meta-elections =====")
         * next_line()
-        * (parse_consensus_history() - next_line()
+        * (parse_consensus_history()
+            + parse_trimmed_history().opt()
+            + parse_abandoned_payloads().opt()
+            - next_line()
             + parse_interesting_events()
             + parse_voters()
             + parse_payload().opt()
@@ -468,11 +505,21 @@ fn parse_meta_election(ctx: &Rc<ParserCtx>) -> Parser<u8, ParsedMetaElection> {
         .map(
             |(
                 (
-                    (((consensus_history, interesting_events), voters), payload),
+                    (
+                        (
+                            (
+                                ((consensus_history, trimmed_history), abandoned_payloads),
+                                interesting_events,
+                            ),
+                            voters,
+                        ),
+                        payload,
+                    ),
                     unconsensused_events,
                 ),
                 observation_map_and_meta_events,
             )| {
+                let (trimmed_history_len, trimmed_payloads) = trimmed_history.unwrap_or_default();
                 let mut observation_map = BTreeMap::new();
                 let mut meta_events = BTreeMap::new();
                 for (id, (obs, m_ev)) in observation_map_and_meta_events {
@@ -488,6 +535,9 @@ fn parse_meta_election(ctx: &Rc<ParserCtx>) -> Parser<u8, ParsedMetaElection> {
                     observation_map,
                     meta_events,
                     consensus_history,
+                    trimmed_history_len,
+                    trimmed_payloads,
+                    abandoned_payloads: abandoned_payloads.unwrap_or_default(),
                 }
             },
         )
@@ -541,8 +591,10 @@ fn parse_observation() -> Parser<u8, Observation<Transaction, PeerId>> {
         | parse_add()
         | parse_remove()
         | parse_opaque()
+        | parse_batch()
         | parse_start_dkg()
         | parse_dkg_msg()
+        | parse_no_op()
 }
 
 fn parse_accusation() -> Parser<u8, (PeerId, MaliceInput)> {
@@ -618,10 +670,23 @@ fn parse_opaque() -> Parser<u8, Observation<Transaction, PeerId>> {
         .map(Observation::OpaquePayload)
 }
 
+fn parse_batch() -> Parser<u8, Observation<Transaction, PeerId>> {
+    (seq(b"Batch(") * list(parse_transaction(), seq(b", ")) - seq(b")"))
+        .map(|payloads| payloads.into_iter().map(Transaction::new).collect())
+        .map(Observation::Batch)
+}
+
 fn parse_start_dkg() -> Parser<u8, Observation<Transaction, PeerId>> {
     (seq(b"StartDkg(") * parse_peers() - seq(b")")).map(Observation::StartDkg)
 }
 
+fn parse_no_op() -> Parser<u8, Observation<Transaction, PeerId>> {
+    (seq(b"NoOp(") * is_a(digit).repeat(1..) - seq(b")"))
+        .convert(String::from_utf8)
+        .convert(|s| u64::from_str(&s))
+        .map(Observation::NoOp)
+}
+
 fn parse_dkg_msg() -> Parser<u8, Observation<Transaction, PeerId>> {
     let parser_u8 = is_a(digit)
         .repeat(1..)
@@ -810,7 +875,7 @@ impl ParsedContents {
     /// whatsoever, so this is useful for simulating all kinds of invalid or malicious situations.
     pub fn add_event(&mut self, event: Event<PeerId>) -> EventIndex {
         let indexed_event = self.graph.insert(event);
-        self.peer_list.add_event(indexed_event);
+        unwrap!(self.peer_list.add_event(indexed_event));
 
         let start_index = indexed_event.event_index().topological_index() + 1;
         self.meta_election.new_consensus_start_index = start_index;
@@ -902,8 +967,9 @@ fn derive_test_name_from_current_thread_name() -> String {
 
 /// For use by functional/unit tests which provide a dot file for the test setup.  This reads and
 /// parses the dot file as per `parse_dot_file()` above, with test name being part of the path.
+/// Tests can use it directly to share the dot file of another test.
 #[cfg(test)]
-fn parse_dot_file_with_test_name(filename: &str, test_name: &str) -> ParsedContents {
+pub(crate) fn parse_dot_file_with_test_name(filename: &str, test_name: &str) -> ParsedContents {
     use std::path::PathBuf;
 
     let mut dot_path = PathBuf::from("input_graphs");
@@ -967,6 +1033,11 @@ fn convert_into_parsed_contents(result: ParsedFile) -> ParsedContents {
             .iter()
             .map(|(key, obs)| (*key, ObservationInfo::new(obs.clone()))),
     );
+    for key in &meta_election.trimmed_payloads {
+        if let Some(info) = parsed_contents.observations.get_mut(key) {
+            info.consensused = true;
+        }
+    }
     let meta_election = convert_to_meta_election(
         meta_election,
         &mut event_indices,
@@ -1057,12 +1128,20 @@ fn convert_to_meta_election(
         indices_by_key: unconsensused_events_keyed_indices,
     };
 
+    let voters = convert_peer_id_set(meta_election.voters, peer_list);
+
     MetaElection {
         meta_events,
-        voters: convert_peer_id_set(meta_election.voters, peer_list),
+        // Dot files don't record past voter sets and removals, so attribute the whole history to
+        // the current voters.
+        voters_history: vec![(0, voters.clone())],
+        removals: PeerIndexMap::default(),
+        voters,
         interesting_events,
         unconsensused_events,
+        abandoned_payloads: meta_election.abandoned_payloads.into_iter().collect(),
         consensus_history: meta_election.consensus_history,
+        trimmed_history_len: meta_election.trimmed_history_len,
         continue_consensus_start_index: 0,
         new_consensus_start_index: 0,
     }
@@ -1157,9 +1236,9 @@ fn create_events(
         );
 
         let indexed_event_ref = parsed_contents.graph.insert(next_event);
-        peer_list.add_event(indexed_event_ref);
+        unwrap!(peer_list.add_event(indexed_event_ref));
         let event_index = indexed_event_ref.event_index();
-        peer_list.record_gossiped_event_by(indexed_event_ref.creator(), event_index);
+        unwrap!(peer_list.record_gossiped_event_by(indexed_event_ref.creator(), event_index));
         let _ = event_indices.insert(ev_id, event_index);
     }
 
@@ -1261,8 +1340,11 @@ fn next_topological_event(
 mod tests {
     use super::*;
     use crate::{
-        dev_utils::{Environment, RngChoice, Schedule, ScheduleOptions},
-        dump_graph::DIR,
+        dev_utils::{
+            create_genesis_parsecs, vote_and_gossip_until_stable, Environment, RngChoice, Schedule,
+            ScheduleOptions,
+        },
+        dump_graph::{DumpGraphMode, DIR, DUMP_MODE},
         gossip::GraphSnapshot,
         maidsafe_utilities::serialisation::deserialise,
         meta_voting::MetaElectionSnapshot,
@@ -1315,4 +1397,34 @@ mod tests {
         }
         assert_ne!(num_of_files, 0u8);
     }
+
+    #[test]
+    fn dot_parser_trimmed_history() {
+        let mut parsecs = create_genesis_parsecs(4, SEED);
+        let payload = Observation::OpaquePayload(Transaction::new("ABCD"));
+        vote_and_gossip_until_stable(&mut parsecs, &payload);
+
+        let mut alice = parsecs.swap_remove(0);
+        while alice.poll().is_some() {}
+        let history_len = unwrap!(alice.trim_consensus_history(0));
+        assert_ne!(history_len, 0);
+
+        let dump_dir = DIR.with(|dir| dir.join("dot_parser_trimmed_history"));
+        alice.set_dump_dir(dump_dir.clone());
+        DUMP_MODE.with(|mode| *mode.borrow_mut() = DumpGraphMode::OnParsecDrop);
+        drop(alice);
+        DUMP_MODE.with(|mode| *mode.borrow_mut() = DumpGraphMode::OnConsensus);
+
+        let dot_file = unwrap!(unwrap!(fs::read_dir(&dump_dir))
+            .filter_map(|entry| entry.ok())
+            .find(|entry| entry.path().extension().map_or(false, |ext| ext == "dot")));
+        let parsed = unwrap!(parse_dot_file(dot_file.path()));
+
+        // The trimmed entries still count towards the history length, and their payloads stay
+        // consensused.
+        assert!(parsed.meta_election.consensus_history().is_empty());
+        assert_eq!(parsed.meta_election.consensus_history_len(), history_len);
+        let key = ObservationKey::Supermajority(ObservationHash::from(&payload));
+        assert!(unwrap!(parsed.observations.get(&key)).consensused);
+    }
 }
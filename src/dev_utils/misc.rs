@@ -6,6 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+#[cfg(test)]
+use crate::{
+    dev_utils::{new_common_rng, new_rng, RngChoice},
+    mock::{self, PeerId, Transaction},
+    observation::{ConsensusMode, Observation},
+    parsec::{gossip_until_stable, TestParsec},
+};
+#[cfg(test)]
+use std::collections::BTreeSet;
 use std::{cmp::Ordering, fmt::Debug};
 
 /// Testing related extensions to `Iterator`.
@@ -49,3 +58,36 @@ pub trait TestIterator: Iterator {
 }
 
 impl<I: Iterator> TestIterator for I {}
+
+/// Creates the instances of a genesis group of `count` peers running in `Supermajority` mode.
+#[cfg(test)]
+pub(crate) fn create_genesis_parsecs(
+    count: usize,
+    seed: RngChoice,
+) -> Vec<TestParsec<Transaction, PeerId>> {
+    let mut common_rng = new_common_rng(seed);
+    let genesis_group = mock::create_ids(count).into_iter().collect::<BTreeSet<_>>();
+    genesis_group
+        .iter()
+        .map(|id| {
+            TestParsec::from_genesis(
+                id.clone(),
+                &genesis_group,
+                ConsensusMode::Supermajority,
+                new_rng(&mut common_rng),
+            )
+        })
+        .collect()
+}
+
+/// Makes each of the given instances vote for `observation`, then gossips until they're stable.
+#[cfg(test)]
+pub(crate) fn vote_and_gossip_until_stable(
+    parsecs: &mut [TestParsec<Transaction, PeerId>],
+    observation: &Observation<Transaction, PeerId>,
+) {
+    for parsec in parsecs.iter_mut() {
+        unwrap!(parsec.vote_for(observation.clone()));
+    }
+    let _ = unwrap!(gossip_until_stable(parsecs, 100));
+}
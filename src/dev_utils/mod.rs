@@ -25,9 +25,11 @@ mod record;
 mod schedule;
 
 #[cfg(test)]
-pub(crate) use self::dot_parser::parse_test_dot_file;
+pub(crate) use self::dot_parser::{parse_dot_file_with_test_name, parse_test_dot_file};
 #[cfg(all(test, feature = "mock"))]
 pub(crate) use self::dot_parser::ParsedContents;
+#[cfg(test)]
+pub(crate) use self::misc::{create_genesis_parsecs, vote_and_gossip_until_stable};
 #[cfg(any(all(test, feature = "mock"), feature = "testing"))]
 pub use self::record::Record;
 pub use self::{
@@ -25,7 +25,7 @@ use itertools::Itertools;
 use rand::Rng;
 use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
-    fmt,
+    fmt, usize,
 };
 
 enum Message {
@@ -425,6 +425,9 @@ impl Network {
             ConsensusMode::Supermajority => {
                 is_more_than_two_thirds(signatories.len(), section.len())
             }
+            ConsensusMode::Threshold { .. } => {
+                consensus_mode.check(signatories.len(), section.len())
+            }
         };
         if !correct_signatories {
             return Err(ConsensusError::TooFewSignatures {
@@ -476,7 +479,9 @@ impl Network {
             .peer(peer_id)
             .unpolled_accusations()
             .find(|(offender, malice)| match malice {
-                Malice::Fork(..) => !self.peer(offender).has_misbehaved(),
+                Malice::Fork(..) | Malice::ForkedRequesting(..) => {
+                    !self.peer(offender).has_misbehaved()
+                }
                 _ => true,
             });
 
@@ -602,7 +607,11 @@ impl Network {
                 if add_type == AddPeerType::Voter && !self.allow_addition_of_peer() {
                     return Ok(false);
                 }
-                let current_peers = self.active_peers().map(|peer| peer.id().clone()).collect();
+                let current_peers = self
+                    .active_peers()
+                    .map(|peer| peer.id().clone())
+                    .take(options.joiner_contacts.unwrap_or(usize::MAX))
+                    .collect();
                 let _ = self.peers.insert(
                     peer_id.clone(),
                     Peer::from_existing(
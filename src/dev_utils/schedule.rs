@@ -282,6 +282,8 @@ pub struct ScheduleOptions {
     pub genesis_restrict_consensus_to: Option<BTreeSet<PeerId>>,
     /// Allows for voting for the same OpaquePayload. This applies only when `ConsensusMode::Single`
     pub vote_for_same: bool,
+    /// The number of current members a joining peer is told about. All if none.
+    pub joiner_contacts: Option<usize>,
 }
 
 impl ScheduleOptions {
@@ -340,6 +342,8 @@ impl Default for ScheduleOptions {
             intermediate_consistency_checks: true,
             genesis_restrict_consensus_to: None,
             vote_for_same: false,
+            // joining peers know the whole section
+            joiner_contacts: None,
         }
     }
 }
@@ -607,7 +611,9 @@ impl Schedule {
                         Sampling::Constant(1)
                     }
                 }
-                ConsensusMode::Supermajority => options.opaque_voters,
+                ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => {
+                    options.opaque_voters
+                }
             };
 
             for obs in opaque_transactions {
@@ -707,7 +713,9 @@ impl Schedule {
                                     Sampling::Constant(1)
                                 }
                             }
-                            ConsensusMode::Supermajority => options.opaque_voters,
+                            ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => {
+                                options.opaque_voters
+                            }
                         };
 
                         pending.peers_make_observation(
@@ -16,7 +16,7 @@ use crate::{
     parsec::KeyGenId,
     peer_list::PeerList,
 };
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::Path};
 
 /// Use this to initialise the folder into which the dot files will be dumped.  This allows the
 /// folder's path to be displayed at the start of a run, rather than at the arbitrary point when
@@ -42,6 +42,8 @@ pub(crate) struct ToFileInfo<'a, T: NetworkEvent, S: SecretId> {
     pub observations: &'a ObservationStore<T, S::PublicId>,
     pub secure_rng: &'a ParsecRng,
     pub key_gens_and_next_id: (&'a BTreeMap<KeyGenId, KeyGen<S>>, &'a KeyGenId),
+    // Overrides the thread's default dump directory if set.
+    pub dump_dir: Option<&'a Path>,
     pub info: &'a DumpGraphContext,
 }
 
@@ -209,7 +211,21 @@ mod detail {
             *count += 1;
             *count
         });
-        let file_path = DIR.with(|dir| dir.join(format!("{}-{:03}.dot", id, call_count)));
+        let file_name = format!("{}-{:03}.dot", id, call_count);
+        let file_path = match info.dump_dir {
+            Some(dir) => {
+                if let Err(error) = fs::create_dir_all(dir) {
+                    println!(
+                        "Failed to create folder {} for dot files: {:?}",
+                        dir.display(),
+                        error
+                    );
+                    return;
+                }
+                dir.join(file_name)
+            }
+            None => DIR.with(|dir| dir.join(file_name)),
+        };
         catch_dump(
             file_path.clone(),
             info.gossip_graph,
@@ -236,6 +252,7 @@ mod detail {
                         &info.peer_list,
                         &short_peer_ids,
                     ),
+                    trimmed_payloads: trimmed_payloads(&info.observations, info.meta_election),
                     peer_ids: &peer_ids,
                     short_peer_ids: &short_peer_ids,
                     indent: 0,
@@ -353,6 +370,22 @@ mod detail {
         lines
     }
 
+    fn trimmed_payloads<T: NetworkEvent, P: PublicId>(
+        observations: &ObservationStore<T, P>,
+        meta_election: &MetaElection,
+    ) -> BTreeSet<ObservationKey> {
+        if meta_election.trimmed_history_len == 0 {
+            return BTreeSet::new();
+        }
+        observations
+            .iter()
+            .filter(|(key, info)| {
+                info.consensused && !meta_election.consensus_history().contains(key)
+            })
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
     struct DotWriter<'a, S: SecretId + 'a> {
         file: BufWriter<File>,
         consensus_mode: ConsensusMode,
@@ -362,6 +395,8 @@ mod detail {
         secure_rng: &'a ParsecRng,
         key_gens_and_next_id: (&'a BTreeMap<KeyGenId, KeyGen<S>>, &'a KeyGenId),
         observations: &'a DotObservationStore,
+        // Consensused payloads whose entries were trimmed from the consensus history.
+        trimmed_payloads: BTreeSet<ObservationKey>,
         peer_ids: &'a PeerIndexMap<DotPeerId>,
         short_peer_ids: &'a PeerIndexMap<String>,
         indent: usize,
@@ -718,6 +753,44 @@ mod detail {
                     key.hash().0.full_display()
                 ));
             }
+            if self.meta_election.trimmed_history_len > 0 {
+                lines.push(format!(
+                    "{}{}trimmed_history_len: {}",
+                    Self::COMMENT,
+                    self.indentation(),
+                    self.meta_election.trimmed_history_len
+                ));
+                lines.push(format!(
+                    "{}{}trimmed_payloads:",
+                    Self::COMMENT,
+                    self.indentation()
+                ));
+                for key in &self.trimmed_payloads {
+                    lines.push(format!(
+                        "{}{}{}",
+                        Self::COMMENT,
+                        self.indentation(),
+                        key.hash().0.full_display()
+                    ));
+                }
+            }
+            if !self.meta_election.abandoned_payloads.is_empty() {
+                lines.push(format!(
+                    "{}{}abandoned_payloads:",
+                    Self::COMMENT,
+                    self.indentation()
+                ));
+                let abandoned_payloads: BTreeSet<_> =
+                    self.meta_election.abandoned_payloads.iter().collect();
+                for hash in abandoned_payloads {
+                    lines.push(format!(
+                        "{}{}{}",
+                        Self::COMMENT,
+                        self.indentation(),
+                        hash.0.full_display()
+                    ));
+                }
+            }
 
             lines.push("".to_string());
 
@@ -1047,6 +1120,14 @@ mod detail {
                     }
                     format!("OpaquePayload({})", payload_str)
                 }
+                Observation::Batch(payloads) => format!(
+                    "Batch({})",
+                    payloads
+                        .iter()
+                        .map(|payload| sanitise_string(format!("{:?}", payload)))
+                        .join(", ")
+                ),
+                Observation::NoOp(number) => format!("NoOp({})", number),
             };
 
             let additional_info = match observation {
@@ -1081,6 +1162,11 @@ mod detail {
             Malice::InvalidAccusation(event_hash) => {
                 format!("InvalidAccusation({})", get_short_name_by_hash(event_hash))
             }
+            Malice::ForkedRequesting(first_hash, second_hash) => format!(
+                "ForkedRequesting({}, {})",
+                get_short_name_by_hash(first_hash),
+                get_short_name_by_hash(second_hash)
+            ),
             Malice::Accomplice(event_hash, boxed_malice) => format!(
                 "Accomplice({}, {})",
                 get_short_name_by_hash(event_hash),
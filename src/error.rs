@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::peer_list::PeerState;
+use crate::{gossip::EventHash, peer_list::PeerState};
 use std::{
     fmt::{self, Display, Formatter},
     result,
@@ -21,6 +21,8 @@ pub enum Error {
     UnknownPayload,
     /// Attempt to create a block with no votes.
     MissingVotes,
+    /// The peer list has no voters.
+    MissingVoters,
     /// Failed to verify signature.
     SignatureFailure,
     /// Peer is not known to our node.
@@ -41,24 +43,108 @@ pub enum Error {
     },
     /// The given event is invalid or malformed.
     InvalidEvent,
+    /// The fields of the received event's content are inconsistent with each other, e.g. its vote
+    /// isn't signed by its creator.
+    MalformedEvent,
     /// The event's self-parent is unknown to our node.
     UnknownSelfParent,
     /// The event's other-parent is unknown to our node.
     UnknownOtherParent,
     /// Our node has already voted for this network event.
     DuplicateVote,
+    /// The observation is invalid, e.g. its payload failed `NetworkEvent::validate`.
+    InvalidObservation,
     /// The peer sent a message to us before knowing we could handle it.
     PrematureGossip,
     /// The request or response is invalid.
     InvalidMessage,
     /// The request or response has already been handled by us.
     DuplicateMessage,
+    /// The request or response carries more events than we are willing to handle.
+    GossipTooLarge,
+    /// The request or response causes us to accuse more peers than we are willing to queue.
+    TooManyAccusations,
+    /// The request or response was encoded with a version of the gossip wire protocol we don't
+    /// support.
+    UnsupportedProtocolVersion {
+        /// Protocol version used by the peer
+        version: u16,
+    },
     /// Faild DKG process
     FailedDkg,
+    /// The serialised observation is larger than the configured maximum observation size.
+    ObservationTooLarge,
+    /// Too many consensused blocks are waiting to be returned by `Parsec::poll`.
+    ConsensusBacklogFull,
+    /// The operation would make us create gossip events, but we are an observer-only node.
+    ObserverMode,
+    /// The consensus history can't be trimmed that far, as blocks waiting to be polled need it.
+    ConsensusHistoryInUse,
+    /// There's no accusation waiting for approval at the given index.
+    UnknownAccusation,
+    /// The instances passed to `gossip_until_stable` didn't become stable within the given number
+    /// of rounds.
+    NotStable,
     /// Logic error.
     Logic,
 }
 
+impl Error {
+    /// Returns the broad category of this error, telling whether it may go away on its own, was
+    /// caused by the peer which sent us the offending data, by our own state or configuration, or
+    /// by the caller.
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            Error::UnknownSelfParent
+            | Error::UnknownOtherParent
+            | Error::DuplicateMessage
+            | Error::ConsensusBacklogFull
+            | Error::NotStable => ErrorKind::Transient,
+            Error::SignatureFailure
+            | Error::InvalidEvent
+            | Error::MalformedEvent
+            | Error::PrematureGossip
+            | Error::InvalidMessage => ErrorKind::PeerMisbehaviour,
+            Error::UnknownPeer
+            | Error::InvalidPeerState { .. }
+            | Error::InvalidSelfState { .. }
+            | Error::MissingVoters
+            | Error::GossipTooLarge
+            | Error::TooManyAccusations
+            | Error::UnsupportedProtocolVersion { .. }
+            | Error::ObserverMode
+            | Error::ConsensusHistoryInUse => ErrorKind::Rejected,
+            Error::MismatchedPayload
+            | Error::UnknownPayload
+            | Error::MissingVotes
+            | Error::DuplicateVote
+            | Error::InvalidObservation
+            | Error::ObservationTooLarge
+            | Error::UnknownAccusation => ErrorKind::InvalidInput,
+            Error::FailedDkg | Error::Logic => ErrorKind::Internal,
+        }
+    }
+}
+
+/// Broad category of an `Error`, as returned by `Error::kind`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    /// Nothing is permanently wrong: the operation may succeed if retried later, e.g. once further
+    /// gossip has filled in the events we are missing, or can safely be ignored.
+    Transient,
+    /// The peer which sent us the message provably misbehaved, e.g. sent events with invalid
+    /// signatures or an invalid message. Retrying with the same data won't help.
+    PeerMisbehaviour,
+    /// The operation isn't allowed given our own state or configuration or that of the peer, e.g.
+    /// the peer hasn't been added to the section yet, or speaks an unsupported protocol version.
+    /// It may succeed once that state changes, but not otherwise.
+    Rejected,
+    /// The caller passed invalid arguments, e.g. voted for the same observation twice.
+    InvalidInput,
+    /// An internal failure of `Parsec`, e.g. a bug or a meta-election which didn't terminate.
+    Internal,
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match *self {
@@ -71,6 +157,7 @@ impl Display for Error {
                 "The payload hash doesn't correspond to any payload known to our node."
             ),
             Error::MissingVotes => write!(f, "Block cannot be created with no votes"),
+            Error::MissingVoters => write!(f, "The peer list has no voters."),
             Error::SignatureFailure => write!(
                 f,
                 "The message or signature might be corrupted, or the signer is wrong."
@@ -87,6 +174,10 @@ impl Display for Error {
                 required, actual
             ),
             Error::InvalidEvent => write!(f, "The given event is invalid or malformed."),
+            Error::MalformedEvent => write!(
+                f,
+                "The fields of the received event's content are inconsistent with each other."
+            ),
             Error::UnknownSelfParent => {
                 write!(f, "The event's self-parent is unknown to this node.")
             }
@@ -94,13 +185,46 @@ impl Display for Error {
                 write!(f, "The event's other-parent is unknown to this node.")
             }
             Error::DuplicateVote => write!(f, "Our node has already voted for this network event."),
+            Error::InvalidObservation => write!(f, "The observation is invalid."),
             Error::PrematureGossip => write!(
                 f,
                 "The peer did not know we could handle a message from it."
             ),
             Error::InvalidMessage => write!(f, "This non-empty message is invalid."),
             Error::DuplicateMessage => write!(f, "This message has already been handled."),
+            Error::GossipTooLarge => write!(
+                f,
+                "This message carries more events than we are willing to handle."
+            ),
+            Error::TooManyAccusations => write!(
+                f,
+                "This message triggers more accusations than we are willing to queue."
+            ),
+            Error::UnsupportedProtocolVersion { version } => write!(
+                f,
+                "This message uses an unsupported protocol version ({}).",
+                version
+            ),
             Error::FailedDkg => write!(f, "The requested DKG could not proceed."),
+            Error::ObservationTooLarge => write!(
+                f,
+                "The observation is larger than the maximum observation size."
+            ),
+            Error::ConsensusBacklogFull => {
+                write!(f, "Too many consensused blocks are waiting to be polled.")
+            }
+            Error::ObserverMode => write!(f, "Observer-only nodes can't vote."),
+            Error::ConsensusHistoryInUse => write!(
+                f,
+                "The consensus history is still needed by blocks waiting to be polled."
+            ),
+            Error::UnknownAccusation => write!(
+                f,
+                "There is no accusation waiting for approval at this index."
+            ),
+            Error::NotStable => {
+                write!(f, "The peers didn't become stable within the rounds given.")
+            }
             Error::Logic => write!(
                 f,
                 "This is a logic error and represents a flaw in the code."
@@ -111,3 +235,46 @@ impl Display for Error {
 
 /// A specialised `Result` type for Parsec.
 pub type Result<T> = result::Result<T, Error>;
+
+/// Violation of an invariant of the gossip graph, as found by `Parsec::validate_graph`. Each
+/// variant carries the hash of the offending event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GraphInconsistency {
+    /// The event's self-parent is not in the graph.
+    MissingSelfParent(EventHash),
+    /// The event's other-parent is not in the graph.
+    MissingOtherParent(EventHash),
+    /// The event's parent doesn't precede it in the topological order.
+    ParentAfterChild(EventHash),
+    /// The event's `index_by_creator` is not one more than its self-parent's.
+    InvalidIndexByCreator(EventHash),
+    /// The event's last ancestors don't match its own index or those of its parents.
+    InvalidLastAncestors(EventHash),
+    /// The event carries a vote for an observation that is not in our observation store.
+    MissingObservation(EventHash),
+}
+
+impl Display for GraphInconsistency {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            GraphInconsistency::MissingSelfParent(hash) => {
+                write!(f, "The self-parent of {:?} is missing.", hash)
+            }
+            GraphInconsistency::MissingOtherParent(hash) => {
+                write!(f, "The other-parent of {:?} is missing.", hash)
+            }
+            GraphInconsistency::ParentAfterChild(hash) => {
+                write!(f, "A parent of {:?} doesn't precede it.", hash)
+            }
+            GraphInconsistency::InvalidIndexByCreator(hash) => {
+                write!(f, "The index by creator of {:?} is invalid.", hash)
+            }
+            GraphInconsistency::InvalidLastAncestors(hash) => {
+                write!(f, "The last ancestors of {:?} are invalid.", hash)
+            }
+            GraphInconsistency::MissingObservation(hash) => {
+                write!(f, "The observation voted for by {:?} is missing.", hash)
+            }
+        }
+    }
+}
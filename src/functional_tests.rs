@@ -8,17 +8,23 @@
 
 use crate::{
     block::Block,
-    dev_utils::{new_common_rng, new_rng, parse_test_dot_file, Record, RngChoice, TestIterator},
+    dev_utils::{
+        create_genesis_parsecs, new_common_rng, new_rng, parse_dot_file_with_test_name,
+        parse_test_dot_file, vote_and_gossip_until_stable, Record, RngChoice, TestIterator,
+    },
     error::Error,
-    gossip::{Event, Graph, GraphSnapshot},
+    gossip::{
+        CausalRelation, Event, Graph, GraphSnapshot, Request, SyncStats,
+        MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION,
+    },
     id::{Proof, PublicId},
-    meta_voting::MetaElectionSnapshot,
+    meta_voting::{BoolSet, MetaElectionSnapshot, MetaVote, Step},
     mock::{self, PeerId, Transaction},
     observation::{ConsensusMode, Observation},
-    parsec::TestParsec,
-    peer_list::{PeerListSnapshot, PeerState},
+    parsec::{gossip_round, gossip_until_stable, Parsec, TestParsec},
+    peer_list::{MembershipChange, PeerIndex, PeerList, PeerListSnapshot, PeerState},
 };
-use std::collections::BTreeSet;
+use std::{cell::RefCell, collections::BTreeSet, iter, rc::Rc};
 
 // Use Fixed seed for functional tests: No randomization.
 static SEED: RngChoice = RngChoice::SeededXor([1, 2, 3, 4]);
@@ -74,6 +80,35 @@ fn from_existing() {
     assert_eq!(parsec.graph().len(), 0);
 }
 
+#[test]
+fn section_members() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut peers = mock::create_ids(6);
+    let our_id = unwrap!(peers.pop());
+    let section: BTreeSet<_> = peers.iter().cloned().collect();
+    let genesis_group: BTreeSet<_> = peers.into_iter().take(3).collect();
+
+    let joiner = TestParsec::<Transaction, _>::from_existing(
+        our_id,
+        &genesis_group,
+        &section,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Only the genesis group is known to be in the section; neither the other section peers nor
+    // us have been consensused yet.
+    assert_eq!(joiner.section_members(), genesis_group);
+
+    let genesis_member = TestParsec::<Transaction, _>::from_genesis(
+        unwrap!(genesis_group.iter().next()).clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    assert_eq!(genesis_member.section_members(), genesis_group);
+}
+
 // TODO: remove this `cfg` once the `maidsafe_utilities` crate with PR 130 is published.
 #[cfg(feature = "testing")]
 #[test]
@@ -152,6 +187,43 @@ fn from_existing_requires_that_section_does_not_contain_us() {
     );
 }
 
+#[test]
+fn from_existing_with_state() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut peers = mock::create_ids(4);
+    let our_id = unwrap!(peers.pop());
+    let peers: BTreeSet<_> = peers.into_iter().collect();
+    let alice_id = unwrap!(peers.iter().next()).clone();
+
+    // By default, a joiner can't create gossip until it's added to the section.
+    let mut joiner = TestParsec::<Transaction, _>::from_existing(
+        our_id.clone(),
+        &peers,
+        &peers,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    assert_eq!(
+        joiner.create_gossip(&alice_id),
+        Err(Error::InvalidSelfState {
+            required: PeerState::SEND,
+            actual: PeerState::RECV
+        })
+    );
+
+    let mut joiner = TestParsec::from(Parsec::<Transaction, _>::from_existing_with_state(
+        our_id,
+        &peers,
+        &peers,
+        PeerState::RECV | PeerState::SEND,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    ));
+    assert_eq!(joiner.graph().len(), 1);
+    let req = unwrap!(joiner.create_gossip(&alice_id));
+    assert_eq!(req.packed_events.len(), 2);
+}
+
 #[test]
 fn from_genesis() {
     let mut common_rng = new_common_rng(SEED);
@@ -165,6 +237,7 @@ fn from_genesis() {
         ConsensusMode::Supermajority,
         new_rng(&mut common_rng),
     );
+    assert_eq!(parsec.consensus_mode(), ConsensusMode::Supermajority);
     // the peer_list should contain the entire genesis group
     assert_eq!(parsec.peer_list().all_ids().count(), peers.len());
     // initial event + genesis_observation
@@ -254,6 +327,14 @@ fn add_peer() {
     assert_eq!(alice.create_gossip(&fred_id), Err(Error::UnknownPeer));
     assert_eq!(alice_snapshot, Snapshot::new(&alice));
 
+    let state_changes = Rc::new(RefCell::new(Vec::new()));
+    let state_changes_clone = Rc::clone(&state_changes);
+    alice.set_on_peer_state_change(move |peer_id, old_state, new_state| {
+        state_changes_clone
+            .borrow_mut()
+            .push((peer_id.clone(), old_state, new_state))
+    });
+
     // Now add E_25, which should result in Alice adding Fred.
     let _e_25_index = unwrap!(alice.add_event(e_25));
     assert!(alice
@@ -261,6 +342,14 @@ fn add_peer() {
         .all_ids()
         .any(|(_, peer_id)| *peer_id == fred_id));
 
+    // The callback was invoked for Fred.
+    let state_changes = state_changes.borrow();
+    let (_, old_state, new_state) = unwrap!(state_changes
+        .iter()
+        .find(|(peer_id, _, _)| *peer_id == fred_id));
+    assert_eq!(*old_state, PeerState::inactive());
+    assert!(new_state.can_vote());
+
     // Construct Fred's Parsec instance.
     let mut fred = TestParsec::from_existing(
         fred_id.clone(),
@@ -280,6 +369,315 @@ fn add_peer() {
     assert!(response.packed_events.len() < fred.graph().len());
 }
 
+#[test]
+fn coin_oracle() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    // An oracle which never overrides the coin doesn't change the outcome.
+    let tosses = Rc::new(RefCell::new(Vec::new()));
+    let tosses_clone = Rc::clone(&tosses);
+    alice.set_coin_oracle(move |round, peer_id| {
+        tosses_clone.borrow_mut().push((round, peer_id.clone()));
+        None
+    });
+
+    unwrap!(alice.add_event(a_last));
+    assert!(!tosses.borrow().is_empty());
+
+    let eric_index = unwrap!(alice.peer_list().get_index(&PeerId::new("Eric")));
+    assert_eq!(
+        alice.peer_list().peer_state(eric_index),
+        PeerState::inactive()
+    );
+
+    // An oracle which returns `Some` forces the coin. To see its effect, make every meta-vote about
+    // Bob wait for the coin of round 0, and check the estimates of Alice's last meta-vote about him.
+    let bob_id = PeerId::new("Bob");
+    let mut last_meta_vote_about_bob = |coin: Option<bool>| {
+        let mut parsed_contents =
+            parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+        let a_last = unwrap!(parsed_contents.remove_last_event());
+        let bob_index = unwrap!(parsed_contents.peer_list.get_index(&bob_id));
+        let waiting_for_coin = MetaVote::new(
+            0,
+            Step::GenuineFlip,
+            BoolSet::Empty,
+            BoolSet::Empty,
+            None,
+            None,
+        );
+        for meta_event in parsed_contents.meta_election.meta_events.values_mut() {
+            if let Some(meta_votes) = meta_event.meta_votes.get_mut(bob_index) {
+                *meta_votes = vec![waiting_for_coin];
+            }
+        }
+
+        let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+        let forced_id = bob_id.clone();
+        alice.set_coin_oracle(move |_, peer_id| if *peer_id == forced_id { coin } else { None });
+        let a_last_index = unwrap!(alice.add_event(a_last));
+
+        // Bob's meta-vote is undecided, so the meta-election is still in progress.
+        let meta_votes = unwrap!(alice.meta_election().populated_meta_votes(a_last_index));
+        *unwrap!(meta_votes.get(bob_index).and_then(|votes| votes.last()))
+    };
+
+    // The default coin of round 0 comes up `false`...
+    assert_eq!(
+        last_meta_vote_about_bob(None),
+        MetaVote::new(
+            0,
+            Step::GenuineFlip,
+            BoolSet::Single(false),
+            BoolSet::Empty,
+            None,
+            None
+        )
+    );
+    // ...but the oracle can force it to `true`.
+    assert_eq!(
+        last_meta_vote_about_bob(Some(true)),
+        MetaVote::new(
+            0,
+            Step::GenuineFlip,
+            BoolSet::Single(true),
+            BoolSet::Empty,
+            None,
+            None
+        )
+    );
+}
+
+#[test]
+fn active_consensus_window() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    let (old_start_index, graph_len) = alice.active_consensus_window();
+    assert!(old_start_index <= graph_len);
+    assert_eq!(graph_len, alice.graph().len());
+
+    // Reaching consensus never moves the start of the window backwards.
+    unwrap!(alice.add_event(a_last));
+    let (new_start_index, graph_len) = alice.active_consensus_window();
+    assert!(new_start_index >= old_start_index);
+    assert!(new_start_index <= graph_len);
+    assert_eq!(graph_len, alice.graph().len());
+}
+
+#[test]
+fn clone_with_id() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    assert_eq!(
+        alice
+            .clone_with_id(PeerId::new("Bob"), new_rng(&mut common_rng))
+            .err(),
+        Some(Error::UnknownPeer)
+    );
+
+    let mut clone = TestParsec::from(unwrap!(
+        alice.clone_with_id(PeerId::new("Alice"), new_rng(&mut common_rng))
+    ));
+    assert_eq!(Snapshot::new(&alice), Snapshot::new(&clone));
+
+    // Only the clone learns about Eric's removal.
+    unwrap!(clone.add_event(a_last));
+    let eric_index = unwrap!(alice.peer_list().get_index(&PeerId::new("Eric")));
+    assert_ne!(
+        alice.peer_list().peer_state(eric_index),
+        PeerState::inactive()
+    );
+    assert_eq!(
+        clone.peer_list().peer_state(eric_index),
+        PeerState::inactive()
+    );
+    assert_ne!(Snapshot::new(&alice), Snapshot::new(&clone));
+}
+
+#[test]
+fn voters_for_payload() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    let eric_id = PeerId::new("Eric");
+    let remove_eric = Parsec::<Transaction, PeerId>::observation_id(&Observation::Remove {
+        peer_id: eric_id.clone(),
+        related_info: vec![],
+    });
+    assert_eq!(alice.voters_for_payload(&remove_eric), None);
+
+    unwrap!(alice.add_event(a_last));
+
+    // Eric was still a voter in the meta-election which decided his removal.
+    let expected_voters = btree_set![
+        PeerId::new("Alice"),
+        PeerId::new("Bob"),
+        PeerId::new("Carol"),
+        PeerId::new("Dave"),
+        eric_id
+    ];
+    assert_eq!(
+        alice.voters_for_payload(&remove_eric),
+        Some(expected_voters)
+    );
+}
+
+#[test]
+fn is_interesting() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents = parse_test_dot_file("alice.dot");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    let remove_eric = Observation::Remove {
+        peer_id: PeerId::new("Eric"),
+        related_info: vec![],
+    };
+    let remove_fred = Observation::Remove {
+        peer_id: PeerId::new("Fred"),
+        related_info: vec![],
+    };
+    assert!(alice.is_interesting(&remove_eric));
+    assert!(!alice.is_interesting(&remove_fred));
+
+    // Once consensused, the payload is no longer interesting in the next meta-election.
+    unwrap!(alice.add_event(a_last));
+    assert!(!alice.is_interesting(&remove_eric));
+}
+
+#[test]
+fn membership_history() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut parsed_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_remove_peer");
+    // The final decision to remove Eric is reached in the last event of Alice.
+    let a_last = unwrap!(parsed_contents.remove_last_event());
+    let mut alice = TestParsec::from_parsed_contents(parsed_contents, new_rng(&mut common_rng));
+
+    let eric_id = PeerId::new("Eric");
+    let removes_eric = |change: &MembershipChange<PeerId>| match change {
+        MembershipChange::Remove { peer_id, .. } => *peer_id == eric_id,
+        MembershipChange::Add { .. } => false,
+    };
+    assert!(!alice.membership_history().iter().any(&removes_eric));
+
+    unwrap!(alice.add_event(a_last));
+    let history = alice.membership_history();
+    assert!(history.last().map_or(false, &removes_eric));
+    assert!(history
+        .iter()
+        .zip(history.iter().skip(1))
+        .all(|(earlier, later)| earlier.block_index() < later.block_index()));
+}
+
+#[test]
+fn recompute_consensus() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut alice = TestParsec::from_parsed_contents(
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_sees"),
+        new_rng(&mut common_rng),
+    );
+
+    // The parsed meta-election may not be fully processed yet, so recompute once first.
+    unwrap!(alice.recompute_consensus());
+    while alice.poll().is_some() {}
+    let snapshot = Snapshot::new(&alice);
+
+    // Recomputing is idempotent and doesn't emit the same blocks again.
+    unwrap!(alice.recompute_consensus());
+    assert_eq!(Snapshot::new(&alice), snapshot);
+    assert!(alice.poll().is_none());
+}
+
+#[test]
+fn abandon_unconsensused() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut parsecs: Vec<TestPeer> = vec![alice_id, bob_id.clone()]
+        .into_iter()
+        .map(|id| {
+            TestParsec::from_genesis(
+                id,
+                &genesis_group,
+                ConsensusMode::Supermajority,
+                new_rng(&mut common_rng),
+            )
+        })
+        .collect();
+
+    // Neither the genesis observation nor Alice's vote can be consensused without Bob.
+    let observation = Observation::OpaquePayload(Transaction::new("ABCD"));
+    unwrap!(parsecs[0].vote_for(observation.clone()));
+    assert_eq!(
+        parsecs[0]
+            .meta_election()
+            .unconsensused_events(None)
+            .count(),
+        2
+    );
+
+    // Internal observations can't be abandoned.
+    let genesis = Observation::Genesis {
+        group: genesis_group,
+        related_info: vec![],
+    };
+    assert_eq!(
+        parsecs[0].abandon_unconsensused(&genesis),
+        Err(Error::InvalidObservation)
+    );
+
+    unwrap!(parsecs[0].abandon_unconsensused(&observation));
+    assert_eq!(
+        parsecs[0]
+            .meta_election()
+            .unconsensused_events(None)
+            .count(),
+        1
+    );
+
+    // It's not waiting for consensus anymore.
+    assert_eq!(
+        parsecs[0].abandon_unconsensused(&observation),
+        Err(Error::UnknownPayload)
+    );
+
+    // Once the genesis observation is consensused, a new vote for it counts again.
+    for _ in 0..100 {
+        if parsecs[0].meta_election().consensus_history_len() > 0 {
+            break;
+        }
+        unwrap!(gossip_round(&mut parsecs));
+    }
+    assert!(parsecs[0].meta_election().consensus_history_len() > 0);
+
+    unwrap!(parsecs[1].vote_for(observation.clone()));
+    unwrap!(gossip_round(&mut parsecs));
+    unwrap!(parsecs[0].abandon_unconsensused(&observation));
+}
+
 #[test]
 fn remove_peer() {
     let mut common_rng = new_common_rng(SEED);
@@ -303,12 +701,24 @@ fn remove_peer() {
         PeerState::inactive()
     );
 
+    let eric_state = alice.peer_list().peer_state(eric_index);
+    let state_changes = Rc::new(RefCell::new(Vec::new()));
+    let state_changes_clone = Rc::clone(&state_changes);
+    alice.set_on_peer_state_change(move |peer_id, old_state, new_state| {
+        state_changes_clone
+            .borrow_mut()
+            .push((peer_id.clone(), old_state, new_state))
+    });
+
     // Add event now which shall result in Alice removing Eric.
     unwrap!(alice.add_event(a_last));
     assert_eq!(
         alice.peer_list().peer_state(eric_index),
         PeerState::inactive()
     );
+    assert!(state_changes
+        .borrow()
+        .contains(&(eric_id.clone(), eric_state, PeerState::inactive())));
 
     // Try calling `create_gossip()` for Eric shall result in error.
     assert_eq!(
@@ -417,6 +827,107 @@ fn unpolled_observations() {
     assert!(unpolled_observations.next().is_none());
 }
 
+#[test]
+fn our_vote_order() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let genesis_group = btree_set![alice_id.clone(), PeerId::new("Bob")];
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let votes = vec![
+        Observation::OpaquePayload(Transaction::new("c")),
+        Observation::OpaquePayload(Transaction::new("a")),
+        Observation::OpaquePayload(Transaction::new("b")),
+    ];
+    for vote in &votes {
+        unwrap!(alice.vote_for(vote.clone()));
+    }
+
+    let vote_order = alice.our_vote_order();
+    assert_eq!(vote_order.len(), 4);
+    match vote_order[0] {
+        Observation::Genesis { ref group, .. } => assert_eq!(*group, genesis_group),
+        ref payload => panic!("Unexpected first vote {:?}", payload),
+    }
+    assert_eq!(
+        vote_order[1..].iter().cloned().cloned().collect::<Vec<_>>(),
+        votes
+    );
+}
+
+#[test]
+fn vote_for_at() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let genesis_group = btree_set![alice_id.clone(), PeerId::new("Bob")];
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    unwrap!(alice.vote_for_at(Observation::OpaquePayload(Transaction::new("a")), 42));
+    let vote_hash = *unwrap!(alice.graph().get(alice.our_last_event_index())).hash();
+    assert_eq!(alice.logical_time(&vote_hash), Some(42));
+
+    unwrap!(alice.vote_for(Observation::OpaquePayload(Transaction::new("b"))));
+    let vote_hash = *unwrap!(alice.graph().get(alice.our_last_event_index())).hash();
+    assert_eq!(alice.logical_time(&vote_hash), None);
+}
+
+#[test]
+fn peek_blocks() {
+    let mut common_rng = new_common_rng(SEED);
+    // Same graph as in `unpolled_observations`: A_17 is the event reaching consensus on
+    // `Add(Eric)`.
+    let mut alice_contents =
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_unpolled_observations");
+    let a_17 = unwrap!(alice_contents.remove_last_event());
+
+    let mut alice = TestParsec::from_parsed_contents(alice_contents, new_rng(&mut common_rng));
+    assert!(alice.last_block().is_none());
+    assert!(alice.peek_next().is_none());
+
+    unwrap!(alice.add_event(a_17));
+
+    let add_eric = Observation::Add {
+        peer_id: PeerId::new("Eric"),
+        related_info: vec![],
+    };
+
+    // Peeking doesn't consume the block.
+    for _ in 0..2 {
+        assert_eq!(*unwrap!(alice.last_block()).payload(), add_eric);
+        assert_eq!(*unwrap!(alice.peek_next()).payload(), add_eric);
+    }
+
+    assert_eq!(*unwrap!(alice.poll()).payload(), add_eric);
+    assert!(alice.last_block().is_none());
+    assert!(alice.peek_next().is_none());
+}
+
+#[test]
+fn block_payload_hash() {
+    let mut alice = Record::from(parse_dot_file_with_test_name(
+        "alice.dot",
+        "functional_tests_our_unpolled_observations_with_consensus_mode_single",
+    ))
+    .play();
+
+    while let Some(block) = alice.poll() {
+        assert_eq!(
+            block.payload_hash(),
+            Parsec::<Transaction, PeerId>::observation_id(block.payload())
+        );
+    }
+}
+
 #[test]
 fn our_unpolled_observations_with_consensus_mode_single() {
     let mut alice = Record::from(parse_test_dot_file("alice.dot")).play();
@@ -503,12 +1014,1312 @@ fn gossip_after_fork() {
     let a_2_fork_hash = *a_2_fork.hash();
     unwrap!(alice.add_event(a_2_fork));
 
-    // Alice sends another gossip request to Bob.
-    let req = unwrap!(alice.create_gossip(&bob_id));
-    let _ = unwrap!(bob.handle_request(&alice_id, req));
+    // Alice sends another gossip request to Bob.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+
+    // Verify that Bob now has the forked event.
+    assert!(bob.graph().contains(&a_2_fork_hash));
+}
+
+#[test]
+fn quorum() {
+    let mut common_rng = new_common_rng(SEED);
+    let mut peers = mock::create_ids(7).into_iter();
+    let our_id = unwrap!(peers.next());
+
+    for size in 1..=7 {
+        let genesis_group = iter::once(our_id.clone())
+            .chain(peers.clone().take(size - 1))
+            .collect();
+        let parsec = TestParsec::<Transaction, _>::from_genesis(
+            our_id.clone(),
+            &genesis_group,
+            ConsensusMode::Supermajority,
+            new_rng(&mut common_rng),
+        );
+
+        assert_eq!(parsec.active_peer_count(), size);
+        let quorum_size = parsec.quorum_size();
+        assert!(parsec.has_quorum(quorum_size));
+        assert!(!parsec.has_quorum(quorum_size - 1));
+    }
+}
+
+#[test]
+fn gossip_too_large() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![
+        alice_id.clone(),
+        bob_id.clone(),
+        PeerId::new("Carol"),
+        PeerId::new("Dave")
+    ];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Bob doesn't know any of Alice's events yet, so the request carries his whole graph.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    bob.set_max_incoming_events(req.packed_events.len() - 1);
+
+    let bob_snapshot = Snapshot::new(&bob);
+    assert_eq!(
+        bob.handle_request(&alice_id, req),
+        Err(Error::GossipTooLarge)
+    );
+    assert_eq!(bob_snapshot, Snapshot::new(&bob));
+
+    // With the limit raised, the same gossip is accepted.
+    bob.set_max_incoming_events(usize::max_value());
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+}
+
+#[test]
+fn invalid_payload_is_rejected() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Bypass the validation in `vote_for` to make Alice vote for an invalid payload.
+    let invalid_payload = Observation::OpaquePayload(Transaction::new(""));
+    let self_parent = alice.our_last_event_index();
+    let invalid_event = unwrap!(alice.new_event_from_observation(self_parent, invalid_payload));
+    let invalid_hash = *invalid_event.hash();
+    let _ = unwrap!(alice.add_event(invalid_event));
+
+    // Bob rejects the invalid vote whether or not malice detection is enabled.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    assert_eq!(bob.handle_request(&alice_id, req), Err(Error::InvalidEvent));
+    assert!(!bob.graph().contains(&invalid_hash));
+}
+
+#[test]
+fn oversized_payload_is_rejected() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    bob.set_max_observation_size(1_000);
+
+    // Alice accepts larger observations than Bob, so she votes for one Bob considers oversized.
+    let oversized_payload = Observation::OpaquePayload(Transaction::new("ABCD".repeat(1_000)));
+    let self_parent = alice.our_last_event_index();
+    let oversized_event = unwrap!(alice.new_event_from_observation(self_parent, oversized_payload));
+    let oversized_hash = *oversized_event.hash();
+    let _ = unwrap!(alice.add_event(oversized_event));
+
+    // Bob rejects the oversized vote whether or not malice detection is enabled.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    assert_eq!(bob.handle_request(&alice_id, req), Err(Error::InvalidEvent));
+    assert!(!bob.graph().contains(&oversized_hash));
+}
+
+#[test]
+fn frontier_request() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    // Bob only lacks Alice's latest events, so only those are sent to him.
+    let frontier_request = bob.create_frontier_request();
+    let req = unwrap!(alice.handle_frontier_request(&bob_id, frontier_request));
+    let num_lacking = alice
+        .graph()
+        .iter()
+        .filter(|event| !bob.graph().contains(event.hash()))
+        .count();
+    assert_eq!(req.len(), num_lacking);
+    assert!(req.len() < alice.graph().len());
+    assert!(req
+        .packed_events
+        .iter()
+        .all(|packed_event| !bob.graph().contains(&packed_event.compute_hash())));
+
+    // The resulting request is handled as any other.
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+}
+
+#[test]
+fn frontier() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Bob doesn't know any of Alice's events yet.
+    assert_eq!(bob.frontier().keys().collect::<Vec<_>>(), vec![&bob_id]);
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+
+    // Alice's graph only holds her own events, the last of which is the one she sent to Bob.
+    let alice_last_hash = *unwrap!(alice.graph().iter().last()).hash();
+    let frontier = alice.frontier();
+    assert_eq!(frontier.len(), 1);
+    assert_eq!(frontier.get(&alice_id), Some(&alice_last_hash));
+
+    let frontier = bob.frontier();
+    assert_eq!(frontier.len(), 2);
+    assert_eq!(frontier.get(&alice_id), Some(&alice_last_hash));
+
+    // Bob's progress points at the same events as his frontier.
+    let progress = bob.peer_progress();
+    assert_eq!(progress.len(), 2);
+    for (peer_id, hash) in &frontier {
+        let index = unwrap!(progress.get(peer_id));
+        assert_eq!(unwrap!(bob.graph().iter().nth(*index)).hash(), hash);
+    }
+}
+
+#[cfg(feature = "timing")]
+#[test]
+fn is_stalled() {
+    use std::time::Duration;
+
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Nothing is waiting for consensus yet.
+    assert!(!alice.is_stalled(Duration::from_secs(0)));
+
+    // Alice's vote can't be consensused without Bob.
+    unwrap!(alice.vote_for(Observation::OpaquePayload(Transaction::new("ABCD"))));
+    assert!(alice.is_stalled(Duration::from_secs(0)));
+    assert!(!alice.is_stalled(Duration::from_secs(3600)));
+}
+
+#[test]
+fn can_handle_gossip_from() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    assert_eq!(alice.can_handle_gossip_from(&bob_id), Ok(()));
+    assert_eq!(
+        alice.can_handle_gossip_from(&PeerId::new("Carol")),
+        Err(Error::UnknownPeer)
+    );
+}
+
+#[test]
+fn pending_gossip_for() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Bob has no record of Alice yet.
+    assert_eq!(bob.pending_gossip_for(&alice_id), Err(Error::Logic));
+    assert_eq!(
+        bob.pending_gossip_for(&PeerId::new("Carol")),
+        Err(Error::UnknownPeer)
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+
+    // Alice only knows her own events, so Bob would send her all of his.
+    let bob_events: Vec<_> = bob
+        .graph()
+        .iter()
+        .filter(|event| event.creator() == PeerIndex::OUR)
+        .map(|event| *event.hash())
+        .collect();
+    assert_eq!(unwrap!(bob.pending_gossip_for(&alice_id)), bob_events);
+}
+
+#[test]
+fn adversarial_events_are_rejected_without_panicking() {
+    use crate::gossip::{EventHash, PackedEvent};
+    use rand::Rng;
+
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let carol_id = PeerId::new("Carol");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let alice_events: Vec<_> = alice
+        .graph()
+        .iter()
+        .map(|event| unwrap!(event.inner().pack(alice.event_context())))
+        .collect();
+    let a_0 = alice_events[0].compute_hash();
+    let a_1 = alice_events[1].compute_hash();
+
+    // Every request is handled by a fresh Bob, so that earlier ones can't affect later ones.
+    let new_bob = || {
+        TestParsec::<Transaction, _>::from_genesis(
+            bob_id.clone(),
+            &genesis_group,
+            ConsensusMode::Supermajority,
+            new_rng(&mut new_common_rng(SEED)),
+        )
+    };
+    let b_0 = *unwrap!(new_bob().graph().iter().next()).hash();
+    let unknown = PackedEvent::new_initial(carol_id.clone()).compute_hash();
+
+    let handle = |event: PackedEvent<Transaction, PeerId>| {
+        let mut packed_events = alice_events.clone();
+        packed_events.push(event);
+        new_bob().handle_request(&alice_id, Request::new(packed_events))
+    };
+
+    // Hand-picked inconsistencies.
+    let cases = vec![
+        (
+            PackedEvent::new_request(alice_id.clone(), b_0, a_1),
+            Error::InvalidEvent,
+        ),
+        (
+            PackedEvent::new_request(alice_id.clone(), a_1, a_0),
+            Error::InvalidEvent,
+        ),
+        (
+            PackedEvent::new_requesting(alice_id.clone(), alice_id.clone(), a_1),
+            Error::MalformedEvent,
+        ),
+        (
+            PackedEvent::new_requesting(alice_id.clone(), bob_id.clone(), unknown),
+            Error::UnknownSelfParent,
+        ),
+        (
+            PackedEvent::new_request(alice_id.clone(), a_1, unknown),
+            Error::UnknownOtherParent,
+        ),
+        (
+            PackedEvent::new_initial(carol_id.clone()),
+            Error::UnknownPeer,
+        ),
+        (
+            PackedEvent::new_requesting(bob_id.clone(), alice_id.clone(), b_0),
+            Error::InvalidEvent,
+        ),
+    ];
+    for (event, expected_error) in cases {
+        assert_eq!(handle(event).map(|_| ()), Err(expected_error));
+    }
+
+    // Randomly assembled events, which must be handled or rejected, but never cause a panic.
+    let creators = [alice_id.clone(), bob_id.clone(), carol_id];
+    let hashes: Vec<EventHash> = vec![a_0, a_1, b_0, unknown];
+    for _ in 0..200 {
+        let creator = creators[common_rng.gen_range(0, creators.len())].clone();
+        let recipient = creators[common_rng.gen_range(0, creators.len())].clone();
+        let self_parent = hashes[common_rng.gen_range(0, hashes.len())];
+        let other_parent = hashes[common_rng.gen_range(0, hashes.len())];
+        let event = match common_rng.gen_range(0, 5) {
+            0 => PackedEvent::new_initial(creator),
+            1 => PackedEvent::new_requesting(creator, recipient, self_parent),
+            2 => PackedEvent::new_request(creator, self_parent, other_parent),
+            3 => PackedEvent::new_response(creator, self_parent, other_parent),
+            _ => PackedEvent::new_observation(
+                creator,
+                self_parent,
+                Observation::OpaquePayload(Transaction::new("fuzz")),
+            ),
+        };
+        let _ = handle(event);
+    }
+}
+
+#[test]
+fn joining_peer_reports_unknown_parents() {
+    use crate::gossip::PackedEvent;
+
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut dave = TestParsec::<Transaction, _>::from_existing(
+        PeerId::new("Dave"),
+        &genesis_group,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Dave defers events by peers he doesn't know yet, but an event whose other-parent is missing
+    // from the gossip is rejected as such.
+    let mut packed_events: Vec<_> = alice
+        .graph()
+        .iter()
+        .map(|event| unwrap!(event.inner().pack(alice.event_context())))
+        .collect();
+    let a_1 = packed_events[1].compute_hash();
+    let unknown = PackedEvent::new_initial(PeerId::new("Carol")).compute_hash();
+    packed_events.push(PackedEvent::new_request(alice_id.clone(), a_1, unknown));
+
+    assert_eq!(
+        dave.handle_request(&alice_id, Request::new(packed_events))
+            .map(|_| ()),
+        Err(Error::UnknownOtherParent)
+    );
+}
+
+#[test]
+fn unsupported_protocol_version() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let mut req = unwrap!(alice.create_gossip(&bob_id));
+    assert_eq!(req.protocol_version(), PROTOCOL_VERSION);
+    req.protocol_version = PROTOCOL_VERSION + 1;
+
+    let bob_snapshot = Snapshot::new(&bob);
+    assert_eq!(
+        bob.handle_request(&alice_id, req),
+        Err(Error::UnsupportedProtocolVersion {
+            version: PROTOCOL_VERSION + 1
+        })
+    );
+    assert_eq!(bob_snapshot, Snapshot::new(&bob));
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let mut res = unwrap!(bob.handle_request(&alice_id, req));
+    res.protocol_version = MIN_SUPPORTED_PROTOCOL_VERSION - 1;
+    assert_eq!(
+        alice.handle_response(&bob_id, res),
+        Err(Error::UnsupportedProtocolVersion {
+            version: MIN_SUPPORTED_PROTOCOL_VERSION - 1
+        })
+    );
+}
+
+#[test]
+fn create_gossip_iter() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Alice doesn't know any of Bob's events yet, so she sends him her whole graph, including the
+    // sync event created by this call.
+    let packed_events =
+        unwrap!(unwrap!(alice.create_gossip_iter(&bob_id)).collect::<Result<Vec<_>, _>>());
+    assert_eq!(packed_events.len(), alice.graph().len());
+    let res = unwrap!(bob.handle_request(&alice_id, Request::new(packed_events)));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    // Now Alice only sends the events Bob doesn't have yet.
+    let packed_events =
+        unwrap!(unwrap!(alice.create_gossip_iter(&bob_id)).collect::<Result<Vec<_>, _>>());
+    assert!(packed_events.len() < alice.graph().len());
+    let _ = unwrap!(bob.handle_request(&alice_id, Request::new(packed_events)));
+}
+
+#[test]
+fn forget_peer_gossip_state() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    let alice_snapshot = Snapshot::new(&alice);
+    unwrap!(alice.forget_peer_gossip_state(&bob_id));
+    assert_eq!(alice_snapshot, Snapshot::new(&alice));
+
+    // Alice sends her whole graph once...
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    assert_eq!(req.len(), alice.graph().len());
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    // ...and then only the events Bob is missing again.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    assert!(req.len() < alice.graph().len());
+
+    assert_eq!(
+        alice.forget_peer_gossip_state(&PeerId::new("Carol")),
+        Err(Error::UnknownPeer)
+    );
+}
+
+#[test]
+fn peer_responsiveness() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let stats = alice.peer_responsiveness();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[&bob_id].num_responses(), 0);
+    assert_eq!(stats[&bob_id].average_response_gap(), None);
+
+    // Bob responds to Alice's request straight away, so Alice handles the response as her next
+    // event after the requesting one.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    let stats = alice.peer_responsiveness();
+    assert_eq!(stats[&bob_id].num_responses(), 1);
+    assert_eq!(stats[&bob_id].average_response_gap(), Some(1.0));
+    assert_eq!(stats[&alice_id].num_responses(), 0);
+}
+
+#[test]
+fn estimated_memory_usage() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let before = alice.estimated_memory_usage();
+    assert!(before.graph() > 0);
+    assert!(before.observations() > 0);
+    assert_eq!(
+        before.total(),
+        before.graph()
+            + before.observations()
+            + before.meta_election()
+            + before.consensused_blocks()
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    unwrap!(alice.handle_response(&bob_id, res));
+
+    // Alice now holds Bob's events too.
+    let after = alice.estimated_memory_usage();
+    assert!(after.graph() > before.graph());
+    assert!(after.meta_election() > 0);
+}
+
+#[test]
+fn validate_graph() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    assert_eq!(alice.validate_graph(), Ok(()));
+
+    unwrap!(alice.vote_for(Observation::OpaquePayload(Transaction::new("one"))));
+    unwrap!(bob.vote_for(Observation::OpaquePayload(Transaction::new("two"))));
+
+    for _ in 0..3 {
+        let req = unwrap!(alice.create_gossip(&bob_id));
+        let res = unwrap!(bob.handle_request(&alice_id, req));
+        unwrap!(alice.handle_response(&bob_id, res));
+    }
+
+    assert_eq!(alice.validate_graph(), Ok(()));
+    assert_eq!(bob.validate_graph(), Ok(()));
+}
+
+#[test]
+fn gossip_until_stable_reaches_consensus() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+
+    let observation = Observation::OpaquePayload(Transaction::new("ABCD"));
+    for parsec in &mut parsecs {
+        unwrap!(parsec.vote_for(observation.clone()));
+    }
+
+    assert_eq!(gossip_until_stable(&mut parsecs, 0), Err(Error::NotStable));
+
+    let num_rounds = unwrap!(gossip_until_stable(&mut parsecs, 100));
+    assert!(num_rounds > 1);
+    let blocks: Vec<Vec<_>> = parsecs
+        .iter_mut()
+        .map(|parsec| {
+            iter::from_fn(|| parsec.poll())
+                .map(|block| (block.index(), block.payload().clone()))
+                .collect()
+        })
+        .collect();
+    assert!(blocks[0].iter().any(|(_, payload)| *payload == observation));
+    assert!(blocks[0].windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(blocks.iter().all(|other| *other == blocks[0]));
+}
+
+#[test]
+fn suppress_historical_blocks() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+    parsecs[0].set_suppress_historical_blocks(u64::max_value());
+
+    let observation = Observation::OpaquePayload(Transaction::new("ABCD"));
+    vote_and_gossip_until_stable(&mut parsecs, &observation);
+
+    // The suppressed blocks are still consensused.
+    assert!(parsecs[0].poll().is_none());
+    assert!(parsecs[1].poll().is_some());
+    assert_eq!(
+        parsecs[0].meta_election().consensus_history().len(),
+        parsecs[1].meta_election().consensus_history().len()
+    );
+}
+
+#[test]
+fn consensused_backlog() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+
+    let observation = Observation::OpaquePayload(Transaction::new("ABCD"));
+    vote_and_gossip_until_stable(&mut parsecs, &observation);
+    assert!(parsecs[1].consensused_backlog() > 0);
+
+    // While the blocks aren't polled, gossip is rejected without affecting our state.
+    parsecs[1].set_max_consensused_backlog(0);
+    let src_id = parsecs[0].our_pub_id().clone();
+    let dst_id = parsecs[1].our_pub_id().clone();
+    let req = unwrap!(parsecs[0].create_gossip(&dst_id));
+    let snapshot = Snapshot::new(&parsecs[1]);
+    assert_eq!(
+        parsecs[1].can_handle_gossip_from(&src_id),
+        Err(Error::ConsensusBacklogFull)
+    );
+    assert_eq!(
+        parsecs[1].handle_request(&src_id, req.clone()),
+        Err(Error::ConsensusBacklogFull)
+    );
+    assert_eq!(Snapshot::new(&parsecs[1]), snapshot);
+
+    while parsecs[1].poll().is_some() {}
+    assert_eq!(parsecs[1].consensused_backlog(), 0);
+    let _ = unwrap!(parsecs[1].handle_request(&src_id, req));
+}
+
+#[test]
+fn trim_consensus_history() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+
+    let first = Observation::OpaquePayload(Transaction::new("ABCD"));
+    vote_and_gossip_until_stable(&mut parsecs, &first);
+    let history_len = parsecs[0].meta_election().consensus_history_len();
+    assert!(history_len > 1);
+
+    // The history of the blocks still waiting to be polled can't be trimmed.
+    assert_eq!(
+        parsecs[0].trim_consensus_history(0),
+        Err(Error::ConsensusHistoryInUse)
+    );
+    assert!(parsecs[0].poll().is_some());
+    assert!(parsecs[0].consensused_backlog() > 0);
+    assert_eq!(
+        parsecs[0].trim_consensus_history(0),
+        Err(Error::ConsensusHistoryInUse)
+    );
+    while parsecs[0].poll().is_some() {}
+    assert_eq!(
+        unwrap!(parsecs[0].trim_consensus_history(1)),
+        history_len - 1
+    );
+    let first_id = Parsec::<Transaction, PeerId>::observation_id(&first);
+    let genesis_group: BTreeSet<_> = mock::create_ids(4).into_iter().collect();
+    assert_eq!(
+        parsecs[0].voters_for_payload(&first_id),
+        Some(genesis_group)
+    );
+
+    // Block indices keep counting the trimmed entries.
+    let second = Observation::OpaquePayload(Transaction::new("EFGH"));
+    vote_and_gossip_until_stable(&mut parsecs, &second);
+    let block = unwrap!(parsecs[0].poll());
+    assert_eq!(*block.payload(), second);
+    assert_eq!(block.index(), history_len as u64);
+}
+
+#[test]
+fn request_verify_signatures() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let mut req = unwrap!(alice.create_gossip(&bob_id));
+    assert_eq!(req.verify_signatures(), Ok(()));
+
+    // Forge the signature of one of the events.
+    req.packed_events[1].resign(&bob_id);
+    let forged_hash = req.packed_events[1].compute_hash();
+    assert_eq!(req.verify_signatures(), Err(forged_hash));
+}
+
+#[test]
+fn observer_only() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+
+    let observation = Observation::OpaquePayload(Transaction::new("ABCD"));
+    vote_and_gossip_until_stable(&mut parsecs, &observation);
+
+    let mut common_rng = new_common_rng(SEED);
+    let genesis_group: BTreeSet<_> = mock::create_ids(4).into_iter().collect();
+    let mut observer = TestParsec::from(Parsec::observer_only(
+        PeerId::new("Olivia"),
+        &genesis_group,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    ));
+    assert_eq!(
+        observer.vote_for(observation.clone()),
+        Err(Error::ObserverMode)
+    );
+
+    let src_id = parsecs[0].our_pub_id().clone();
+    let packed_events = parsecs[0]
+        .graph()
+        .iter()
+        .map(|event| parsecs[0].pack_event(event.inner()))
+        .collect();
+    let _ = unwrap!(observer.handle_request(&src_id, Request::new(packed_events)));
+
+    // The observer took the events in without creating any of its own...
+    assert_eq!(observer.graph().len(), parsecs[0].graph().len());
+    assert!(observer.peer_list().our_events().next().is_none());
+
+    // ...and reached the same consensus.
+    let payloads = |parsec: &mut TestPeer| {
+        let mut payloads = vec![];
+        while let Some(block) = parsec.poll() {
+            payloads.push(block.payload().clone());
+        }
+        payloads
+    };
+    let expected = payloads(&mut parsecs[0]);
+    assert!(expected.contains(&observation));
+    assert_eq!(payloads(&mut observer), expected);
+}
+
+#[test]
+fn duplicate_request() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let res = unwrap!(bob.handle_request(&alice_id, req.clone()));
+    assert!(!res.is_empty());
+    let bob_graph_len = bob.graph().len();
+
+    // Handling the same request again doesn't create another sync event.
+    let res = unwrap!(bob.handle_request(&alice_id, req));
+    assert!(res.is_empty());
+    assert_eq!(bob.graph().len(), bob_graph_len);
+}
+
+#[test]
+fn sync_stats() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // None of Alice's events are known to Bob yet.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let num_req_events = req.len();
+    let (res, stats) = unwrap!(bob.handle_request_with_stats(&alice_id, req.clone()));
+    assert_eq!(stats.new_events(), num_req_events);
+    assert_eq!(stats.accusations_raised(), 0);
+
+    // None of Bob's events are known to Alice yet.
+    let num_res_events = res.len();
+    let stats = unwrap!(alice.handle_response_with_stats(&bob_id, res));
+    assert_eq!(stats.new_events(), num_res_events);
+    assert_eq!(stats.accusations_raised(), 0);
+
+    // A redelivered request teaches Bob nothing.
+    let (res, stats) = unwrap!(bob.handle_request_with_stats(&alice_id, req));
+    assert!(res.is_empty());
+    assert_eq!(stats, SyncStats::default());
+}
+
+#[test]
+fn causal_relation() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+
+    let a_0 = *nth_event(alice.graph(), 0).hash();
+    let b_0 = *unwrap!(bob
+        .graph()
+        .get(unwrap!(bob.peer_list().our_events().next())))
+    .hash();
+    let b_last = *unwrap!(bob.graph().get(bob.our_last_event_index())).hash();
+
+    assert_eq!(bob.causal_relation(&a_0, &a_0), Some(CausalRelation::Same));
+    assert_eq!(
+        bob.causal_relation(&a_0, &b_0),
+        Some(CausalRelation::Concurrent)
+    );
+    assert_eq!(
+        bob.causal_relation(&a_0, &b_last),
+        Some(CausalRelation::AncestorOf)
+    );
+    assert_eq!(
+        bob.causal_relation(&b_last, &b_0),
+        Some(CausalRelation::DescendantOf)
+    );
+
+    // Alice doesn't know Bob's events yet.
+    assert_eq!(alice.causal_relation(&a_0, &b_0), None);
+}
+
+#[test]
+fn all_observations() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![alice_id.clone(), bob_id.clone()];
+
+    let mut alice = TestParsec::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let payload = Observation::OpaquePayload(Transaction::new("one"));
+    unwrap!(alice.vote_for(payload.clone()));
+    assert!(bob
+        .all_observations()
+        .all(|(observation, _)| *observation != payload));
+
+    // Bob sees Alice's vote once she gossips to him, even though he hasn't voted for it himself.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+    assert!(bob
+        .our_unpolled_observations()
+        .all(|observation| *observation != payload));
+    assert!(bob
+        .all_observations()
+        .any(|(observation, consensused)| *observation == payload && !consensused));
+}
+
+#[test]
+fn import_events() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    let genesis_group = btree_set![
+        alice_id.clone(),
+        bob_id.clone(),
+        PeerId::new("Carol"),
+        PeerId::new("Dave")
+    ];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let bob_events: Vec<_> = bob
+        .graph()
+        .iter()
+        .map(|event| bob.pack_event(event.inner()))
+        .collect();
+
+    // Skipping Bob's initial event leaves the rest without their self-parent.
+    let alice_snapshot = Snapshot::new(&alice);
+    assert_eq!(
+        alice.import_events(bob_events[1..].to_vec()),
+        Err(Error::UnknownSelfParent)
+    );
+    assert_eq!(alice_snapshot, Snapshot::new(&alice));
+
+    let alice_graph_len = alice.graph().len();
+    assert_eq!(
+        unwrap!(alice.import_events(bob_events.clone())),
+        bob_events.len()
+    );
+    assert_eq!(alice.graph().len(), alice_graph_len + bob_events.len());
+
+    // Importing the same events again is a no-op.
+    assert_eq!(unwrap!(alice.import_events(bob_events)), 0);
+}
+
+#[test]
+fn peers_needing_gossip() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+    let carol_id = PeerId::new("Carol");
+    let dave_id = PeerId::new("Dave");
+
+    let genesis_group = btree_set![
+        alice_id.clone(),
+        bob_id.clone(),
+        carol_id.clone(),
+        dave_id.clone()
+    ];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+    let mut bob = TestParsec::<Transaction, _>::from_genesis(
+        bob_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    // Alice doesn't know about any events of the others yet.
+    assert_eq!(
+        alice.peers_needing_gossip(),
+        vec![&bob_id, &carol_id, &dave_id]
+    );
+
+    // Bob receives Alice's latest event. Once Alice learns about Bob's resulting sync event
+    // (without creating a new event herself), Bob no longer needs gossip.
+    let req = unwrap!(alice.create_gossip(&bob_id));
+    let _ = unwrap!(bob.handle_request(&alice_id, req));
+    let bob_events = bob
+        .graph()
+        .iter()
+        .map(|event| bob.pack_event(event.inner()))
+        .collect();
+    let _ = unwrap!(alice.import_events(bob_events));
+
+    assert_eq!(alice.peers_needing_gossip(), vec![&carol_id, &dave_id]);
+}
+
+#[test]
+fn from_peer_list() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let bob_id = PeerId::new("Bob");
+
+    // A peer list with no voters is rejected.
+    let mut peer_list = PeerList::new(alice_id.clone());
+    unwrap!(peer_list.insert_peer(alice_id.clone(), PeerState::SEND | PeerState::RECV));
+    assert_eq!(
+        Parsec::<Transaction, _>::from_peer_list(
+            alice_id.clone(),
+            peer_list,
+            ConsensusMode::Supermajority,
+            new_rng(&mut common_rng),
+        )
+        .err(),
+        Some(Error::MissingVoters)
+    );
+
+    // A peer list we are not in is rejected.
+    let mut peer_list = PeerList::new(bob_id.clone());
+    unwrap!(peer_list.insert_peer(bob_id.clone(), PeerState::active()));
+    assert_eq!(
+        Parsec::<Transaction, _>::from_peer_list(
+            alice_id.clone(),
+            peer_list,
+            ConsensusMode::Supermajority,
+            new_rng(&mut common_rng),
+        )
+        .err(),
+        Some(Error::UnknownPeer)
+    );
+
+    let mut peer_list = PeerList::new(alice_id.clone());
+    unwrap!(peer_list.insert_peer(alice_id.clone(), PeerState::active()));
+    unwrap!(peer_list.insert_peer(bob_id.clone(), PeerState::active()));
+    let alice = TestParsec::from(unwrap!(Parsec::<Transaction, _>::from_peer_list(
+        alice_id.clone(),
+        peer_list,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    )));
+
+    assert!(alice.can_vote());
+    assert_eq!(alice.graph().len(), 1);
+    assert_eq!(alice.section_members(), btree_set![alice_id, bob_id]);
+}
+
+#[test]
+fn vote_to_leave() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let genesis_group = btree_set![
+        alice_id.clone(),
+        PeerId::new("Bob"),
+        PeerId::new("Carol"),
+        PeerId::new("Dave")
+    ];
+
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id.clone(),
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    unwrap!(alice.vote_to_leave());
+    assert!(alice.have_voted_for(&Observation::Remove {
+        peer_id: alice_id.clone(),
+        related_info: vec![],
+    }));
+    assert_eq!(alice.vote_to_leave(), Err(Error::DuplicateVote));
+    assert!(!alice.was_removed());
+
+    // Pretend Alice decides on her own removal at her last event.
+    let removal_event_index = alice.our_last_event_index();
+    alice.remove_peer(&alice_id, removal_event_index);
+    assert!(alice.was_removed());
+    assert_eq!(
+        alice.create_gossip(&PeerId::new("Bob")),
+        Err(Error::InvalidSelfState {
+            required: PeerState::SEND,
+            actual: PeerState::inactive()
+        })
+    );
+}
+
+#[test]
+fn respond_after_removal() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+    let alice_id = parsecs[0].our_pub_id().clone();
+    let dave_id = parsecs[3].our_pub_id().clone();
+    let removal = Observation::Remove {
+        peer_id: alice_id.clone(),
+        related_info: vec![],
+    };
+
+    // Alice leaves, and everyone but Dave reaches consensus on it without him.
+    unwrap!(parsecs[0].vote_to_leave());
+    for parsec in &mut parsecs[1..3] {
+        unwrap!(parsec.vote_for(removal.clone()));
+    }
+    let _ = unwrap!(gossip_until_stable(&mut parsecs[..3], 100));
+    assert!(parsecs[0].was_removed());
+    assert!(iter::from_fn(|| parsecs[0].poll()).any(|block| *block.payload() == removal));
+
+    // Alice still answers Dave's request, which lets him learn of her removal too.
+    let req = unwrap!(parsecs[3].create_gossip(&alice_id));
+    let resp = unwrap!(parsecs[0].handle_request(&dave_id, req));
+    assert!(!resp.is_empty());
+    unwrap!(parsecs[3].handle_response(&alice_id, resp));
+    assert!(iter::from_fn(|| parsecs[3].poll()).any(|block| *block.payload() == removal));
+}
+
+#[test]
+fn vote_by_removed_peer_counts_towards_consensus() {
+    let mut parsecs = create_genesis_parsecs(4, SEED);
+    let bob_id = parsecs[1].our_pub_id().clone();
+    let dave_id = parsecs[3].our_pub_id().clone();
+    let payload = Observation::OpaquePayload(Transaction::new("ABCD"));
+    let removal = Observation::Remove {
+        peer_id: dave_id.clone(),
+        related_info: vec![],
+    };
+
+    // Dave's vote only reaches Bob.
+    unwrap!(parsecs[3].vote_for(payload.clone()));
+    let req = unwrap!(parsecs[3].create_gossip(&bob_id));
+    let resp = unwrap!(parsecs[1].handle_request(&dave_id, req));
+    unwrap!(parsecs[3].handle_response(&bob_id, resp));
+
+    // The others remove Dave.
+    for parsec in &mut parsecs[..3] {
+        unwrap!(parsec.vote_for(removal.clone()));
+    }
+    let is_dave_removed = |parsec: &TestPeer| {
+        let dave_index = unwrap!(parsec.peer_list().get_index(&dave_id));
+        parsec.peer_list().peer_state(dave_index) == PeerState::inactive()
+    };
+    for _ in 0..100 {
+        if parsecs[..3].iter().all(is_dave_removed) {
+            break;
+        }
+        unwrap!(gossip_round(&mut parsecs[..3]));
+    }
+    assert!(parsecs[..3].iter().all(is_dave_removed));
+
+    // His vote alone isn't a supermajority of the voters of the meta-election he cast it in.
+    for parsec in &parsecs[..3] {
+        assert!(!parsec
+            .consensused_blocks()
+            .any(|block| *block.payload() == payload));
+    }
 
-    // Verify that Bob now has the forked event.
-    assert!(bob.graph().contains(&a_2_fork_hash));
+    // Alice's and Bob's votes are only a supermajority of the remaining voters together with
+    // Dave's, which he cast while still being a voter.
+    for parsec in &mut parsecs[..2] {
+        unwrap!(parsec.vote_for(payload.clone()));
+    }
+    let _ = unwrap!(gossip_until_stable(&mut parsecs[..3], 100));
+
+    for parsec in &mut parsecs[..3] {
+        let block =
+            unwrap!(iter::from_fn(|| parsec.poll()).find(|block| *block.payload() == payload));
+        assert!(block.is_signed_by(&dave_id));
+    }
 }
 
 #[test]
@@ -549,6 +2360,168 @@ fn sees() {
     assert!(!a3.sees(c2_1));
 }
 
+#[test]
+fn forks() {
+    let mut common_rng = new_common_rng(SEED);
+    // This graph contains a fork by Carol: C_2,0 and C_2,1 are both self-children of C_1.
+    let alice = TestParsec::from_parsed_contents(
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_sees"),
+        new_rng(&mut common_rng),
+    );
+
+    let c1 = unwrap!(alice.graph().find_by_short_name("C_1"));
+    assert_eq!(
+        alice.forks(),
+        vec![(PeerId::new("Carol"), vec![*c1.hash()])]
+    );
+}
+
+#[test]
+fn meta_event_info() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice = TestParsec::from_parsed_contents(
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_sees"),
+        new_rng(&mut common_rng),
+    );
+
+    // A_1 precedes the current meta-election, so it has no meta-event.
+    let a1 = unwrap!(alice.graph().find_by_short_name("A_1"));
+    assert!(alice.meta_event_info(a1.hash()).is_none());
+
+    let a3 = unwrap!(alice.graph().find_by_short_name("A_3"));
+    let info = unwrap!(alice.meta_event_info(a3.hash()));
+    assert!(!info.is_observer());
+    assert_eq!(info.num_observees(), 0);
+    assert_eq!(info.interesting_content().len(), 1);
+    match info.interesting_content()[0] {
+        Observation::Genesis { ref group, .. } => assert_eq!(
+            *group,
+            btree_set![
+                PeerId::new("Alice"),
+                PeerId::new("Bob"),
+                PeerId::new("Carol")
+            ]
+        ),
+        ref payload => panic!("Unexpected interesting content {:?}", payload),
+    }
+    assert_eq!(
+        unwrap!(alice.interesting_content_order(a3.hash())),
+        info.interesting_content()
+    );
+    assert!(alice.interesting_content_order(a1.hash()).is_none());
+}
+
+#[test]
+fn export_meta_votes() {
+    use crate::meta_voting::MetaVoteSnapshot;
+    use maidsafe_utilities::serialisation;
+
+    let mut common_rng = new_common_rng(SEED);
+    let alice = TestParsec::from_parsed_contents(
+        parse_test_dot_file("alice.dot"),
+        new_rng(&mut common_rng),
+    );
+    let snapshot = alice.export_meta_votes();
+
+    // A_1 precedes the current meta-election, so it has no meta-votes.
+    let a1 = unwrap!(alice.graph().find_by_short_name("A_1"));
+    assert!(!snapshot.meta_votes.contains_key(a1.hash()));
+    assert!(snapshot
+        .meta_votes
+        .values()
+        .flat_map(|meta_votes_by_voter| meta_votes_by_voter.values())
+        .any(|meta_votes| !meta_votes.is_empty()));
+
+    let serialised = unwrap!(serialisation::serialise(&snapshot));
+    assert_eq!(
+        unwrap!(serialisation::deserialise::<MetaVoteSnapshot<PeerId>>(
+            &serialised
+        )),
+        snapshot
+    );
+}
+
+#[test]
+fn consensus_confidence() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice = TestParsec::from_parsed_contents(
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_sees"),
+        new_rng(&mut common_rng),
+    );
+
+    // A_3 carries Alice's genesis vote in its interesting content.
+    let a3 = unwrap!(alice.graph().find_by_short_name("A_3"));
+    let genesis = unwrap!(alice.meta_event_info(a3.hash())).interesting_content()[0].clone();
+    let confidence = alice.consensus_confidence(&genesis);
+    assert_eq!(confidence.num_voters(), 3);
+    assert!(confidence.num_interesting() >= 1);
+    assert!(confidence.num_decided() <= confidence.num_interesting());
+
+    let unknown = Observation::OpaquePayload(Transaction::new("unknown"));
+    let confidence = alice.consensus_confidence(&unknown);
+    assert_eq!(confidence.num_voters(), 3);
+    assert_eq!(confidence.num_interesting(), 0);
+    assert_eq!(confidence.num_decided(), 0);
+}
+
+#[test]
+fn observers() {
+    let mut common_rng = new_common_rng(SEED);
+    let alice = TestParsec::from_parsed_contents(
+        parse_dot_file_with_test_name("alice.dot", "functional_tests_sees"),
+        new_rng(&mut common_rng),
+    );
+
+    let observers = alice.observers();
+    for hash in &observers {
+        let info = unwrap!(alice.meta_event_info(hash));
+        assert!(info.is_observer());
+        assert_eq!(unwrap!(alice.observees(hash)).len(), info.num_observees());
+    }
+
+    let non_observers = alice
+        .graph()
+        .iter()
+        .map(|event| *event.hash())
+        .filter(|hash| !observers.contains(hash));
+    for hash in non_observers {
+        assert!(alice
+            .meta_event_info(&hash)
+            .map_or(true, |info| !info.is_observer()));
+    }
+}
+
+#[cfg(feature = "dump-graphs")]
+#[test]
+fn set_dump_dir() {
+    use crate::dump_graph::{DumpGraphMode, DIR, DUMP_MODE};
+    use std::fs;
+
+    let mut common_rng = new_common_rng(SEED);
+    let alice_id = PeerId::new("Alice");
+    let genesis_group = btree_set![alice_id.clone(), PeerId::new("Bob")];
+    let mut alice = TestParsec::<Transaction, _>::from_genesis(
+        alice_id,
+        &genesis_group,
+        ConsensusMode::Supermajority,
+        new_rng(&mut common_rng),
+    );
+
+    let dump_dir = DIR.with(|dir| dir.join("set_dump_dir_alice"));
+    alice.set_dump_dir(dump_dir.clone());
+
+    // Dropping Alice should dump her graph into her own directory rather than the shared one.
+    DUMP_MODE.with(|mode| *mode.borrow_mut() = DumpGraphMode::OnParsecDrop);
+    drop(alice);
+    DUMP_MODE.with(|mode| *mode.borrow_mut() = DumpGraphMode::OnConsensus);
+
+    let dot_files = unwrap!(fs::read_dir(&dump_dir))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "dot"))
+        .count();
+    assert_eq!(dot_files, 1);
+}
+
 #[cfg(feature = "malice-detection")]
 mod handle_malice {
     use super::*;
@@ -556,9 +2529,10 @@ mod handle_malice {
         dev_utils::{parse_test_dot_file, ParsedContents},
         gossip::{Event, EventHash},
         id::SecretId,
-        mock::{self, Transaction},
+        mock::Transaction,
         network_event::NetworkEvent,
-        observation::Malice,
+        observation::{Malice, MaliceKind, UnprovableMalice},
+        parsec::STALE_GOSSIP_LIMIT,
         peer_list::{PeerIndex, PeerList, PeerState},
         PackedEvent, Request, Response,
     };
@@ -593,9 +2567,9 @@ mod handle_malice {
     ) {
         for peer_id in genesis {
             if let Some(index) = peer_list.get_index(peer_id) {
-                peer_list.change_peer_state(index, PeerState::active())
+                unwrap!(peer_list.change_peer_state(index, PeerState::active()))
             } else {
-                let _ = peer_list.add_peer(peer_id.clone(), PeerState::active());
+                let _ = unwrap!(peer_list.add_peer(peer_id.clone(), PeerState::active()));
             }
         }
     }
@@ -617,9 +2591,9 @@ mod handle_malice {
         let dave_id = PeerId::new("Dave");
         let mut dave_contents = ParsedContents::new(dave_id.clone());
 
-        dave_contents
+        unwrap!(dave_contents
             .peer_list
-            .change_peer_state(PeerIndex::OUR, PeerState::active());
+            .change_peer_state(PeerIndex::OUR, PeerState::active()));
         add_genesis_group(&mut dave_contents.peer_list, &genesis);
 
         let d_0 = Event::new_initial(dave_contents.event_context());
@@ -683,9 +2657,9 @@ mod handle_malice {
         let eric_id = PeerId::new("Eric");
         let mut eric_contents = ParsedContents::new(eric_id.clone());
 
-        eric_contents
+        unwrap!(eric_contents
             .peer_list
-            .change_peer_state(PeerIndex::OUR, PeerState::active());
+            .change_peer_state(PeerIndex::OUR, PeerState::active()));
         add_genesis_group(&mut eric_contents.peer_list, &genesis);
 
         let e_0 = Event::new_initial(eric_contents.event_context());
@@ -722,22 +2696,6 @@ mod handle_malice {
         assert_eq!(hash, e_1_hash);
     }
 
-    fn initialise_genesis_parsecs(count: usize) -> Vec<TestPeer> {
-        let mut common_rng = new_common_rng(SEED);
-        let genesis_ids = mock::create_ids(count).into_iter().collect::<BTreeSet<_>>();
-        genesis_ids
-            .iter()
-            .map(|id| {
-                TestParsec::from_genesis(
-                    id.clone(),
-                    &genesis_ids,
-                    ConsensusMode::Supermajority,
-                    new_rng(&mut common_rng),
-                )
-            })
-            .collect()
-    }
-
     // Asserts that these and only these accusations have been made by `peer`.
     fn assert_peer_has_accused(
         peer: &TestPeer,
@@ -754,10 +2712,18 @@ mod handle_malice {
         assert_eq!(expected_accusations, actual_accusations);
     }
 
+    fn forked_requesting(lhs: EventHash, rhs: EventHash) -> Malice<Transaction, PeerId> {
+        if lhs < rhs {
+            Malice::ForkedRequesting(lhs, rhs)
+        } else {
+            Malice::ForkedRequesting(rhs, lhs)
+        }
+    }
+
     #[test]
     fn missing_genesis_event() {
         let (mut alice, mut bob) =
-            unwrap!(initialise_genesis_parsecs(2).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
 
         // Pop Alice's last event, which is her genesis vote.
         let (_, genesis_event) = unwrap!(alice.remove_last_event());
@@ -772,19 +2738,222 @@ mod handle_malice {
         let alice_requesting_hash = *nth_event(alice.graph(), 1).hash();
 
         // Send request.
-        unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        let (_, stats) = unwrap!(bob.handle_request_with_stats(alice.our_pub_id(), request));
         assert!(bob.graph().contains(&alice_initial_hash));
         assert!(bob.graph().contains(&alice_requesting_hash));
 
         // Verify that Bob detected and accused Alice of malice.
         let expected_malice = Malice::MissingGenesis(alice_requesting_hash);
         assert_peer_has_accused(&bob, vec![(alice.our_pub_id(), &expected_malice)]);
+        assert_eq!(stats.accusations_raised(), 1);
+    }
+
+    #[test]
+    fn too_many_accusations() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        // Pop Alice's last event, which is her genesis vote, so that Bob accuses her on receipt of
+        // her next event.
+        let _ = unwrap!(alice.remove_last_event());
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let alice_requesting_hash = *nth_event(alice.graph(), 1).hash();
+
+        bob.set_max_pending_accusations(0);
+        assert_eq!(
+            bob.handle_request(alice.our_pub_id(), request),
+            Err(Error::TooManyAccusations)
+        );
+        assert!(bob.graph().contains(&alice_requesting_hash));
+        assert!(bob.pending_accusations().is_empty());
+        assert_peer_has_accused(&bob, vec![]);
+
+        // The accusation over the limit was dropped, so Bob handles the next message from Alice
+        // without raising the limit.
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let alice_next_requesting_hash = *unwrap!(alice.graph().iter().last()).hash();
+        unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        assert!(bob.graph().contains(&alice_next_requesting_hash));
+        assert_peer_has_accused(&bob, vec![]);
+    }
+
+    #[test]
+    fn manual_accusation_approval() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        // Pop Alice's last event, which is her genesis vote, so that Bob detects malice on receipt
+        // of her next event.
+        let _ = unwrap!(alice.remove_last_event());
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let alice_requesting_hash = *nth_event(alice.graph(), 1).hash();
+
+        bob.set_manual_accusation_approval(true);
+        unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        let expected_malice = Malice::MissingGenesis(alice_requesting_hash);
+        assert_eq!(
+            bob.review_pending_accusations(),
+            &[(alice.our_pub_id().clone(), expected_malice.clone())]
+        );
+        assert!(bob.pending_accusations().is_empty());
+        assert_peer_has_accused(&bob, vec![]);
+        assert_eq!(bob.approve_accusation(1), Err(Error::UnknownAccusation));
+
+        // Once approved, the accusation is raised when Bob handles the next message.
+        unwrap!(bob.approve_accusation(0));
+        assert!(bob.review_pending_accusations().is_empty());
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        assert_peer_has_accused(&bob, vec![(alice.our_pub_id(), &expected_malice)]);
+    }
+
+    #[test]
+    fn manual_accusation_approval_limit() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        let _ = unwrap!(alice.remove_last_event());
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+
+        // The accusation over the limit is dropped, and the message is still handled.
+        bob.set_manual_accusation_approval(true);
+        bob.set_max_pending_accusations(0);
+        unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        assert!(bob.review_pending_accusations().is_empty());
+        assert!(bob.pending_accusations().is_empty());
+    }
+
+    #[test]
+    fn manual_accusation_dismissal() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+        bob.set_manual_accusation_approval(true);
+
+        // Set up Bob to receive stale gossip from Alice, as in `stale_gossip`.
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let mut stale_events = request.packed_events.clone();
+        let _ = stale_events.pop();
+        let response = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        unwrap!(alice.handle_response(bob.our_pub_id(), response));
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let mut response = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+
+        let expected_malice = Malice::Unprovable(UnprovableMalice::Spam);
+        for attempt in 0..2 {
+            for _ in 0..STALE_GOSSIP_LIMIT {
+                let request = Request::new(stale_events.clone());
+                assert_eq!(
+                    bob.handle_request(alice.our_pub_id(), request).map(|_| ()),
+                    Err(Error::InvalidMessage)
+                );
+            }
+
+            if attempt == 0 {
+                // The first time round, the malice is held back for review and dismissed...
+                assert_eq!(
+                    bob.review_pending_accusations(),
+                    &[(alice.our_pub_id().clone(), expected_malice.clone())]
+                );
+                unwrap!(bob.dismiss_accusation(0));
+            } else {
+                // ...so detecting it again doesn't hold it back anymore.
+                assert!(bob.review_pending_accusations().is_empty());
+            }
+
+            // A fresh message from Alice resets her count of stale messages.
+            unwrap!(alice.handle_response(bob.our_pub_id(), response));
+            let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+            response = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        }
+
+        assert_peer_has_accused(&bob, vec![]);
+    }
+
+    #[test]
+    fn stale_gossip() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let redelivered_events = request.packed_events.clone();
+        // All but the last event of the request, which Bob never synced with.
+        let mut stale_events = request.packed_events.clone();
+        let _ = stale_events.pop();
+        let response = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        unwrap!(alice.handle_response(bob.our_pub_id(), response));
+
+        // Alice's next message carries her new sync event, which raises Bob's watermark for her
+        // above all the events of her first message.
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let response = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+
+        // Redeliveries are benign, however many of them there are...
+        for _ in 0..STALE_GOSSIP_LIMIT {
+            let request = Request::new(redelivered_events.clone());
+            let _ = unwrap!(bob.handle_request(alice.our_pub_id(), request));
+        }
+
+        // ...and isolated stale messages are tolerated...
+        for _ in 1..STALE_GOSSIP_LIMIT {
+            let request = Request::new(stale_events.clone());
+            assert_eq!(
+                bob.handle_request(alice.our_pub_id(), request).map(|_| ()),
+                Err(Error::InvalidMessage)
+            );
+        }
+        assert!(bob.pending_accusations().is_empty());
+        assert_peer_has_accused(&bob, vec![]);
+
+        // ...but a sustained pattern of them is not.
+        let request = Request::new(stale_events);
+        assert_eq!(
+            bob.handle_request(alice.our_pub_id(), request).map(|_| ()),
+            Err(Error::InvalidMessage)
+        );
+
+        // Make sure the accusation gets raised. It was detected in the stale message, so it doesn't
+        // count towards the stats of this one.
+        unwrap!(alice.handle_response(bob.our_pub_id(), response));
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        let (_, stats) = unwrap!(bob.handle_request_with_stats(alice.our_pub_id(), request));
+        assert_eq!(stats.accusations_raised(), 0);
+
+        let expected_malice = Malice::Unprovable(UnprovableMalice::Spam);
+        assert_peer_has_accused(&bob, vec![(alice.our_pub_id(), &expected_malice)]);
+    }
+
+    #[test]
+    fn malformed_event() {
+        let (alice, mut bob) = unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+        let alice_id = alice.our_pub_id().clone();
+
+        // A requesting event addressed to its own creator can't be produced by `create_gossip`.
+        let mut packed_events = take_packed_events(&alice, alice.graph().len());
+        let self_parent = unwrap!(packed_events.last()).compute_hash();
+        packed_events.push(PackedEvent::new_requesting(
+            alice_id.clone(),
+            alice_id.clone(),
+            self_parent,
+        ));
+
+        assert_eq!(
+            bob.handle_request(&alice_id, Request::new(packed_events)),
+            Err(Error::MalformedEvent)
+        );
+        let alice_index = unwrap!(bob.peer_list().get_index(&alice_id));
+        assert_eq!(
+            *bob.pending_accusations(),
+            vec![(
+                alice_index,
+                Malice::Unprovable(UnprovableMalice::Unspecified)
+            )]
+        );
     }
 
     #[test]
     fn incorrect_genesis_event() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Pop Alice's last event, which is her genesis vote, and replace with a vote for a
         // different genesis group.
@@ -833,6 +3002,164 @@ mod handle_malice {
         assert!(carol.pending_accusations().is_empty());
     }
 
+    #[test]
+    fn invalid_payload() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        let invalid_payload = Observation::OpaquePayload(Transaction::new(""));
+        assert_eq!(
+            alice.vote_for(invalid_payload.clone()),
+            Err(Error::InvalidObservation)
+        );
+
+        // Bypass the validation in `vote_for` to make Alice vote for the invalid payload anyway.
+        let self_parent = alice.our_last_event_index();
+        let invalid_event = unwrap!(alice.new_event_from_observation(self_parent, invalid_payload));
+        let invalid_hash = *invalid_event.hash();
+        let invalid_packed = alice.pack_event(&invalid_event);
+        let _ = unwrap!(alice.add_event(invalid_event));
+
+        // Send request from Alice to Bob. The invalid vote should be rejected.
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        assert_eq!(
+            bob.handle_request(alice.our_pub_id(), request),
+            Err(Error::InvalidEvent)
+        );
+        assert!(!bob.graph().contains(&invalid_hash));
+
+        // Bob should have a pending accusation against Alice's event.
+        assert_eq!(bob.pending_accusations().len(), 1);
+        let alice_index = unwrap!(bob.get_peer_index(alice.our_pub_id()));
+        let pending_accusation = &bob.pending_accusations()[0];
+        assert_eq!(alice_index, pending_accusation.0);
+        let expected_malice = Malice::InvalidPayload(Box::new(invalid_packed));
+        assert_eq!(expected_malice, pending_accusation.1);
+    }
+
+    #[test]
+    fn oversized_payload() {
+        let (mut alice, mut bob) =
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
+
+        let oversized_payload = Observation::OpaquePayload(Transaction::new("ABCD".repeat(1_000)));
+        alice.set_max_observation_size(1_000);
+        bob.set_max_observation_size(1_000);
+        assert_eq!(
+            alice.vote_for(oversized_payload.clone()),
+            Err(Error::ObservationTooLarge)
+        );
+
+        // Bypass the check in `vote_for` to make Alice vote for the oversized payload anyway.
+        let self_parent = alice.our_last_event_index();
+        let oversized_event =
+            unwrap!(alice.new_event_from_observation(self_parent, oversized_payload));
+        let oversized_hash = *oversized_event.hash();
+        let _ = unwrap!(alice.add_event(oversized_event));
+
+        // Send request from Alice to Bob. The oversized vote should be rejected.
+        let request = unwrap!(alice.create_gossip(bob.our_pub_id()));
+        assert_eq!(
+            bob.handle_request(alice.our_pub_id(), request),
+            Err(Error::InvalidEvent)
+        );
+        assert!(!bob.graph().contains(&oversized_hash));
+
+        // Bob should have a pending accusation against Alice's event.
+        assert_eq!(bob.pending_accusations().len(), 1);
+        let alice_index = unwrap!(bob.get_peer_index(alice.our_pub_id()));
+        let pending_accusation = &bob.pending_accusations()[0];
+        assert_eq!(alice_index, pending_accusation.0);
+        assert_eq!(
+            Malice::Unprovable(UnprovableMalice::Spam),
+            pending_accusation.1
+        );
+    }
+
+    #[test]
+    fn accusation_stats() {
+        let mut parsecs = create_genesis_parsecs(4, SEED);
+        assert!(parsecs[0].accusation_stats().is_empty());
+
+        // Everyone but Dave accuses him, then they gossip without him.
+        let accusation = Observation::Accusation {
+            offender: parsecs[3].our_pub_id().clone(),
+            malice: Malice::Unprovable(UnprovableMalice::Spam),
+        };
+        for parsec in &mut parsecs[..3] {
+            unwrap!(parsec.vote_for(accusation.clone()));
+        }
+        let _ = unwrap!(gossip_until_stable(&mut parsecs[..3], 100));
+
+        let stats = parsecs[0].accusation_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats.get(&MaliceKind::Unprovable), Some(&1));
+    }
+
+    #[test]
+    fn forged_accusation() {
+        let (mut alice, bob, mut carol) =
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
+
+        // Alice accuses Bob using an event she claims was created by Bob, but which she signed
+        // herself.
+        let b_0 = nth_event(bob.graph(), 0);
+        let mut forged_event =
+            PackedEvent::new_response(bob.our_pub_id().clone(), *b_0.hash(), *b_0.hash());
+        forged_event.resign(alice.our_pub_id());
+        let forged_accusation = Observation::Accusation {
+            offender: bob.our_pub_id().clone(),
+            malice: Malice::OtherParentBySameCreator(Box::new(forged_event)),
+        };
+        let self_parent = alice.our_last_event_index();
+        let accusation_event =
+            unwrap!(alice.new_event_from_observation(self_parent, forged_accusation));
+        let accusation_hash = *accusation_event.hash();
+        let _ = unwrap!(alice.add_event(accusation_event));
+
+        // Send request from Alice to Carol. Carol should accuse Alice of the invalid accusation.
+        let request = unwrap!(alice.create_gossip(carol.our_pub_id()));
+        let _ = unwrap!(carol.handle_request(alice.our_pub_id(), request));
+
+        assert_peer_has_accused(
+            &carol,
+            vec![(
+                alice.our_pub_id(),
+                &Malice::InvalidAccusation(accusation_hash),
+            )],
+        );
+    }
+
+    #[test]
+    fn gossip_with_removed_peer() {
+        let (mut alice, mut bob, _carol, mut dave) =
+            unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
+
+        // Bob syncs with Alice, so he sees the event at which Alice will decide on Dave's removal.
+        let request = unwrap!(bob.create_gossip(alice.our_pub_id()));
+        let response = unwrap!(alice.handle_request(bob.our_pub_id(), request));
+        let removal_event_index = alice.our_last_event_index();
+        unwrap!(bob.handle_response(alice.our_pub_id(), response));
+
+        // Then Bob syncs with Dave.
+        let request = unwrap!(dave.create_gossip(bob.our_pub_id()));
+        let _ = unwrap!(bob.handle_request(dave.our_pub_id(), request));
+        let sync_hash = *unwrap!(bob.graph().get(bob.our_last_event_index())).hash();
+
+        // Alice removes Dave and then receives Bob's sync event with Dave. Bob hasn't decided on
+        // Dave's removal himself, so the event is legitimate.
+        alice.remove_peer(dave.our_pub_id(), removal_event_index);
+        let request = unwrap!(bob.create_gossip(alice.our_pub_id()));
+        let _ = unwrap!(alice.handle_request(bob.our_pub_id(), request));
+        assert!(alice.graph().contains(&sync_hash));
+
+        let bob_index = unwrap!(alice.get_peer_index(bob.our_pub_id()));
+        assert!(alice
+            .pending_accusations()
+            .iter()
+            .all(|(offender, _)| *offender != bob_index));
+    }
+
     fn assert_handling_invalid_response(
         sender: &mut TestPeer,
         receiver: &mut TestPeer,
@@ -866,7 +3193,7 @@ mod handle_malice {
     #[test]
     fn invalid_request_wrong_recipient() {
         let (mut alice, mut bob, carol, mut dave) =
-            unwrap!(initialise_genesis_parsecs(4).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
 
         // Create request from Alice to Carol, but sent to Bob.
         let request_msg = unwrap!(alice.create_gossip(carol.our_pub_id()));
@@ -887,7 +3214,7 @@ mod handle_malice {
         let invalid_req_hash = invalid_req.compute_hash();
         let mut packed_events = take_packed_events(&bob, bob.graph().len());
         packed_events.push(invalid_req.clone());
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
 
         let expected_malice = Malice::InvalidRequest(Box::new(invalid_req));
 
@@ -911,7 +3238,7 @@ mod handle_malice {
     #[test]
     fn invalid_request_wrong_type_of_other_parent() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Send request from Alice to Bob.
         let request_msg = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -935,7 +3262,7 @@ mod handle_malice {
 
         let mut packed_events = take_packed_events(&bob, 2);
         packed_events.push(invalid_req.clone());
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
 
         assert_handling_invalid_response(
             &mut bob,
@@ -948,7 +3275,7 @@ mod handle_malice {
         packed_events = take_packed_events(&bob, 5);
         packed_events.push(invalid_req);
         // Knowledge of Alice and Bob, and the invalid_req.
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
 
         assert_handling_invalid_response(
             &mut bob,
@@ -962,7 +3289,7 @@ mod handle_malice {
     #[test]
     fn duplicate_requests() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Send request from Alice to Bob.
         let request_msg = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -987,7 +3314,7 @@ mod handle_malice {
 
         let mut packed_events = take_packed_events(&bob, bob.graph().len());
         packed_events.push(invalid_req);
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
         assert_handling_invalid_response(
             &mut bob,
             &mut carol,
@@ -1008,7 +3335,7 @@ mod handle_malice {
     #[test]
     fn invalid_response_wrong_recipient() {
         let (mut alice, mut bob, mut carol, mut dave) =
-            unwrap!(initialise_genesis_parsecs(4).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
 
         // Send request from Alice to Bob.
         let request_msg = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -1017,7 +3344,7 @@ mod handle_malice {
         // If the response be sent to Carol, a response event shall not be created.
         let packed_events = take_packed_events(&bob, bob.graph().len());
         assert_eq!(
-            carol.handle_response(bob.our_pub_id(), Response { packed_events }),
+            carol.handle_response(bob.our_pub_id(), Response::new(packed_events)),
             Err(Error::InvalidMessage)
         );
         assert!(!carol.graph().iter().any(|event| event.is_response()));
@@ -1035,7 +3362,7 @@ mod handle_malice {
         let mut packed_events = take_packed_events(&carol, 2);
         packed_events.push(bob_request);
         packed_events.push(invalid_resp.clone());
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
         assert_handling_invalid_response(
             &mut carol,
             &mut bob,
@@ -1047,7 +3374,7 @@ mod handle_malice {
         packed_events = take_packed_events(&bob, 8);
         packed_events.push(invalid_resp);
         // Knowledge of Alice, Bob and Carol, and the invalid_resp.
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
         assert_handling_invalid_response(
             &mut carol,
             &mut dave,
@@ -1060,7 +3387,7 @@ mod handle_malice {
     #[test]
     fn invalid_response_wrong_type_of_other_parent() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Send request from Alice to Bob.
         let request_msg = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -1079,9 +3406,7 @@ mod handle_malice {
         let invalid_resp_hash = invalid_resp.compute_hash();
         let expected_malice = Malice::InvalidResponse(Box::new(invalid_resp.clone()));
 
-        let invalid_response_msg = Response {
-            packed_events: vec![invalid_resp.clone()],
-        };
+        let invalid_response_msg = Response::new(vec![invalid_resp.clone()]);
         assert_handling_invalid_response(
             &mut alice,
             &mut bob,
@@ -1093,7 +3418,7 @@ mod handle_malice {
         let mut packed_events = take_packed_events(&bob, 6);
         packed_events.push(invalid_resp);
         // Knowledge of Alice and Bob, and the invalid_resp.
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
         assert_handling_invalid_response(
             &mut alice,
             &mut carol,
@@ -1106,7 +3431,7 @@ mod handle_malice {
     #[test]
     fn duplicate_responses() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Send request from Alice to Bob.
         let request_msg = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -1126,9 +3451,7 @@ mod handle_malice {
         let invalid_resp_hash = invalid_resp.compute_hash();
         let expected_malice = Malice::InvalidResponse(Box::new(invalid_resp.clone()));
 
-        let invalid_response_msg = Response {
-            packed_events: vec![valid_resp, invalid_resp.clone()],
-        };
+        let invalid_response_msg = Response::new(vec![valid_resp, invalid_resp.clone()]);
         assert_handling_invalid_response(
             &mut alice,
             &mut bob,
@@ -1139,7 +3462,7 @@ mod handle_malice {
 
         let mut packed_events = take_packed_events(&alice, alice.graph().len());
         packed_events.push(invalid_resp);
-        let invalid_response_msg = Response { packed_events };
+        let invalid_response_msg = Response::new(packed_events);
         assert_handling_invalid_response(
             &mut alice,
             &mut carol,
@@ -1218,7 +3541,7 @@ mod handle_malice {
     impl AccompliceEnvironment {
         fn new() -> Self {
             let (mut alice, mut bob, mut carol, dave) =
-                unwrap!(initialise_genesis_parsecs(4).into_iter().collect_tuple());
+                unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
 
             // Put Carol's events into Alice's graph, and have Alice make a false accusation of
             // forking by Carol's last event.
@@ -1388,10 +3711,20 @@ mod handle_malice {
         unwrap!(dave.handle_request(bob.our_pub_id(), message));
         // Dave should now be aware of the other branch of the fork.
         assert!(dave.graph().contains(&bob_a_21_hash));
+        assert_eq!(dave.fork_count(alice0.our_pub_id()), 1);
+        assert_eq!(dave.fork_count(bob.our_pub_id()), 0);
 
         // Verify that Dave detected malice and accused Alice of it.
+        // Both of Alice's A_21 are requesting events, but for different recipients.
         let expected_malice = Malice::Fork(*unwrap!(bob.graph().find_by_short_name("A_20")).hash());
-        assert_peer_has_accused(&dave, vec![(alice0.our_pub_id(), &expected_malice)]);
+        let expected_forked_requesting = forked_requesting(bob_a_21_hash, dave_a_21_hash);
+        assert_peer_has_accused(
+            &dave,
+            vec![
+                (alice0.our_pub_id(), &expected_malice),
+                (alice0.our_pub_id(), &expected_forked_requesting),
+            ],
+        );
     }
 
     #[test]
@@ -1406,7 +3739,7 @@ mod handle_malice {
     // Fork accusations should be made against A_1 and A_2,1.
     fn second_fork_on_branch_of_first_fork_four_peers() {
         let (alice, mut bob, mut carol, mut dave) =
-            unwrap!(initialise_genesis_parsecs(4).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
 
         let alice_id = alice.our_pub_id().clone();
         let bob_id = bob.our_pub_id().clone();
@@ -1418,9 +3751,7 @@ mod handle_malice {
         let a_1 = unwrap!(nth_event(alice.graph(), 1).pack(alice.event_context()));
         let a_2_0 =
             PackedEvent::new_requesting(alice_id.clone(), bob_id.clone(), a_1.compute_hash());
-        let mut request = Request {
-            packed_events: vec![a_0.clone(), a_1.clone(), a_2_0.clone()],
-        };
+        let mut request = Request::new(vec![a_0.clone(), a_1.clone(), a_2_0.clone()]);
         unwrap!(bob.handle_request(&alice_id, request.clone()));
 
         // [A_0, A_1, A_2,1, A_3,0] will be sent to Carol.
@@ -1440,12 +3771,21 @@ mod handle_malice {
         request.packed_events = vec![a_0.clone(), a_1.clone(), a_2_1.clone(), a_3_1.clone()];
         unwrap!(dave.handle_request(&alice_id, request));
 
-        // Send a request from Dave to Carol.  Carol should accuse A_2_1.  Don't send the response.
+        // Send a request from Dave to Carol.  Carol should accuse A_2_1, and A_3,0 and A_3,1 of
+        // being requesting events for different recipients.  Don't send the response.
         request = unwrap!(dave.create_gossip(&carol_id));
         unwrap!(carol.handle_request(&dave_id, request));
         assert!(carol.graph().contains(&a_3_1.compute_hash()));
         let expected_malice_a_2_1 = Malice::Fork(a_2_1.compute_hash());
-        assert_peer_has_accused(&carol, vec![(&alice_id, &expected_malice_a_2_1)]);
+        let expected_forked_requesting =
+            forked_requesting(a_3_0.compute_hash(), a_3_1.compute_hash());
+        assert_peer_has_accused(
+            &carol,
+            vec![
+                (&alice_id, &expected_malice_a_2_1),
+                (&alice_id, &expected_forked_requesting),
+            ],
+        );
 
         // Send a request from Dave to Bob.  Bob should accuse A_1.  Don't send the response.
         request = unwrap!(dave.create_gossip(&bob_id));
@@ -1454,8 +3794,8 @@ mod handle_malice {
         let expected_malice_a_1 = Malice::Fork(a_1.compute_hash());
         assert_peer_has_accused(&bob, vec![(&alice_id, &expected_malice_a_1)]);
 
-        // Send a request from Carol to Bob and send the response.  Bob should accuse A_2_1 and
-        // Carol should accuse A_1.
+        // Send a request from Carol to Bob and send the response.  Bob should accuse A_2_1 and the
+        // forked requesting events, and Carol should accuse A_1.
         request = unwrap!(carol.create_gossip(&bob_id));
         let response = unwrap!(bob.handle_request(&carol_id, request));
         assert!(bob.graph().contains(&a_3_0.compute_hash()));
@@ -1463,6 +3803,7 @@ mod handle_malice {
         let both_accusations = vec![
             (&alice_id, &expected_malice_a_1),
             (&alice_id, &expected_malice_a_2_1),
+            (&alice_id, &expected_forked_requesting),
         ];
         assert_peer_has_accused(&bob, both_accusations.clone());
         unwrap!(carol.handle_response(&bob_id, response));
@@ -1492,7 +3833,7 @@ mod handle_malice {
     // https://github.com/maidsafe/parsec/pull/295#discussion_r273378041
     fn second_fork_on_branch_of_first_fork_three_peers() {
         let (alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         let alice_id = alice.our_pub_id().clone();
         let bob_id = bob.our_pub_id().clone();
@@ -1515,9 +3856,8 @@ mod handle_malice {
             PackedEvent::new_requesting(alice_id.clone(), bob_id.clone(), a_2_0.compute_hash());
         let a_3_1 =
             PackedEvent::new_requesting(alice_id.clone(), bob_id.clone(), a_2_1.compute_hash());
-        let mut request = Request {
-            packed_events: vec![a_0.clone(), a_1.clone(), a_2_0.clone(), a_3_0.clone()],
-        };
+        let mut request =
+            Request::new(vec![a_0.clone(), a_1.clone(), a_2_0.clone(), a_3_0.clone()]);
         unwrap!(bob.handle_request(&alice_id, request.clone()));
 
         // [A_0, A_1, A_2,1, A_3,1] will be sent to Bob second.  Bob should accuse A_1.
@@ -1535,7 +3875,8 @@ mod handle_malice {
         request.packed_events = vec![a_0.clone(), a_1.clone(), a_2_1.clone(), a_3_2.clone()];
         unwrap!(carol.handle_request(&alice_id, request));
 
-        // Send a request from Bob to Carol.  Carol should accuse A_1 and A_2_1.
+        // Send a request from Bob to Carol.  Carol should accuse A_1 and A_2_1, and A_3,1 and A_3,2
+        // of being requesting events for different recipients.
         request = unwrap!(bob.create_gossip(&carol_id));
         let response = unwrap!(carol.handle_request(&bob_id, request));
         assert!(carol.graph().contains(&a_2_0.compute_hash()));
@@ -1544,9 +3885,12 @@ mod handle_malice {
         assert!(carol.graph().contains(&a_3_1.compute_hash()));
         assert!(carol.graph().contains(&a_3_2.compute_hash()));
         let expected_malice_a_2_1 = Malice::Fork(a_2_1.compute_hash());
+        let expected_forked_requesting =
+            forked_requesting(a_3_1.compute_hash(), a_3_2.compute_hash());
         let both_accusations = vec![
             (&alice_id, &expected_malice_a_1),
             (&alice_id, &expected_malice_a_2_1),
+            (&alice_id, &expected_forked_requesting),
         ];
         assert_peer_has_accused(&carol, both_accusations.clone());
 
@@ -1568,7 +3912,7 @@ mod handle_malice {
     // Fork accusations should be made against A_1 only.
     fn triple_fork() {
         let (alice, mut bob, mut carol, mut dave) =
-            unwrap!(initialise_genesis_parsecs(4).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(4, SEED).into_iter().collect_tuple());
 
         let alice_id = alice.our_pub_id().clone();
         let bob_id = bob.our_pub_id().clone();
@@ -1580,9 +3924,7 @@ mod handle_malice {
         let a_1 = unwrap!(nth_event(alice.graph(), 1).pack(alice.event_context()));
         let a_2_0 =
             PackedEvent::new_requesting(alice_id.clone(), bob_id.clone(), a_1.compute_hash());
-        let mut request = Request {
-            packed_events: vec![a_0.clone(), a_1.clone(), a_2_0.clone()],
-        };
+        let mut request = Request::new(vec![a_0.clone(), a_1.clone(), a_2_0.clone()]);
         unwrap!(bob.handle_request(&alice_id, request.clone()));
 
         // [A_0, A_1, A_2,1] will be sent to Carol.
@@ -1597,27 +3939,42 @@ mod handle_malice {
         request.packed_events = vec![a_0.clone(), a_1.clone(), a_2_2.clone()];
         unwrap!(dave.handle_request(&alice_id, request));
 
+        // A_2,0, A_2,1 and A_2,2 are requesting events for different recipients, but each peer
+        // accuses only the first pair of them it sees.
+        let expected_malice = Malice::Fork(a_1.compute_hash());
+        let forked_requesting_0_1 = forked_requesting(a_2_0.compute_hash(), a_2_1.compute_hash());
+        let forked_requesting_1_2 = forked_requesting(a_2_1.compute_hash(), a_2_2.compute_hash());
+
         // Send a request from Bob to Carol and send the response.  Bob and Carol should accuse A_1.
         request = unwrap!(bob.create_gossip(&carol_id));
         let mut response = unwrap!(carol.handle_request(&bob_id, request));
         assert!(carol.graph().contains(&a_2_0.compute_hash()));
-        let expected_malice = Malice::Fork(a_1.compute_hash());
-        let expected_accusation = vec![(&alice_id, &expected_malice)];
+        let expected_accusation = vec![
+            (&alice_id, &expected_malice),
+            (&alice_id, &forked_requesting_0_1),
+        ];
         assert_peer_has_accused(&carol, expected_accusation.clone());
         unwrap!(bob.handle_response(&carol_id, response));
         assert!(bob.graph().contains(&a_2_1.compute_hash()));
         assert_peer_has_accused(&bob, expected_accusation.clone());
 
-        // Send a request from Dave to Carol and send the response.  Dave should accuse A_1.  Carol
-        // should not make any further accusations.
+        // Send a request from Dave to Carol and send the response.  Dave should accuse A_1 and the
+        // first pair of forked requesting events it receives, which is A_2,1 and A_2,2 as Carol
+        // has A_2,1 before A_2,0.  Carol should not make any further accusations.
         request = unwrap!(dave.create_gossip(&carol_id));
         response = unwrap!(carol.handle_request(&dave_id, request));
         assert!(carol.graph().contains(&a_2_2.compute_hash()));
-        assert_peer_has_accused(&carol, expected_accusation.clone());
+        assert_peer_has_accused(&carol, expected_accusation);
         unwrap!(dave.handle_response(&carol_id, response));
         assert!(dave.graph().contains(&a_2_0.compute_hash()));
         assert!(dave.graph().contains(&a_2_1.compute_hash()));
-        assert_peer_has_accused(&dave, expected_accusation.clone());
+        assert_peer_has_accused(
+            &dave,
+            vec![
+                (&alice_id, &expected_malice),
+                (&alice_id, &forked_requesting_1_2),
+            ],
+        );
     }
 
     #[derive(PartialEq)]
@@ -1628,7 +3985,7 @@ mod handle_malice {
 
     fn invalid_parent_creator_test(test_type: InvalidCreatorFor) {
         let (mut alice, bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Create invalid B_2 by Bob - either a `Request` event with other-parent as his first event
         // or an `Observation` with self-parent as Carol's second event.
@@ -1652,13 +4009,11 @@ mod handle_malice {
         }
 
         // Send Bob's message to Alice.  B_2 should be rejected as invalid.
-        let message = Request {
-            packed_events: vec![
-                unwrap!(b_0.pack(bob.event_context())),
-                unwrap!(b_1.pack(bob.event_context())),
-                b_2_packed.clone(),
-            ],
-        };
+        let message = Request::new(vec![
+            unwrap!(b_0.pack(bob.event_context())),
+            unwrap!(b_1.pack(bob.event_context())),
+            b_2_packed.clone(),
+        ]);
         assert_eq!(
             alice.handle_request(bob.our_pub_id(), message),
             Err(Error::InvalidEvent)
@@ -1764,7 +4119,7 @@ mod handle_malice {
     #[test]
     fn missing_self_parent() {
         let (mut alice, mut bob) =
-            unwrap!(initialise_genesis_parsecs(2).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(2, SEED).into_iter().collect_tuple());
 
         // Request message contains `[Initial, Genesis, Requesting]`.
         let mut request = unwrap!(alice.create_gossip(bob.our_pub_id()));
@@ -1792,7 +4147,7 @@ mod handle_malice {
     #[test]
     fn missing_other_parent() {
         let (mut alice, mut bob, mut carol) =
-            unwrap!(initialise_genesis_parsecs(3).into_iter().collect_tuple());
+            unwrap!(create_genesis_parsecs(3, SEED).into_iter().collect_tuple());
 
         // Put Bob's events into Alice's graph.
         let message = unwrap!(bob.create_gossip(alice.our_pub_id()));
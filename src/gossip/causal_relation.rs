@@ -0,0 +1,20 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+/// Causal relationship of one gossip event to another.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CausalRelation {
+    /// Both are the same event.
+    Same,
+    /// The first event is an ancestor of the second one.
+    AncestorOf,
+    /// The first event is a descendant of the second one.
+    DescendantOf,
+    /// Neither event is an ancestor of the other one.
+    Concurrent,
+}
@@ -67,11 +67,27 @@ impl<V, E, P> Content<V, E, P> {
     }
 }
 
+impl<T: NetworkEvent, P: PublicId> Content<Vote<T, P>, EventHash, P> {
+    // Returns whether the cause is consistent with the creator. The regular constructors always
+    // produce consistent content, so an inconsistency means the event was hand-crafted.
+    fn is_well_formed(&self) -> bool {
+        match self.cause {
+            Cause::Requesting { ref recipient, .. } => *recipient != self.creator,
+            Cause::Observation { ref vote, .. } => vote.is_valid(&self.creator),
+            Cause::Request { .. } | Cause::Response { .. } | Cause::Initial => true,
+        }
+    }
+}
+
 impl<P: PublicId> Content<VoteKey<P>, EventIndex, PeerIndex> {
     pub(crate) fn unpack<T: NetworkEvent, S: SecretId<PublicId = P>>(
         packed_content: Content<Vote<T, P>, EventHash, P>,
         ctx: EventContextRef<T, S>,
     ) -> Result<(Self, ObservationForStore<T, P>), Error> {
+        if !packed_content.is_well_formed() {
+            return Err(Error::MalformedEvent);
+        }
+
         let creator = ctx
             .peer_list
             .get_index(&packed_content.creator)
@@ -33,8 +33,12 @@ use crate::{
     observation::ConsensusMode,
 };
 use itertools::Itertools;
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    mem,
+};
 
+#[derive(Clone)]
 pub(crate) struct Event<P: PublicId> {
     content: Content<VoteKey<P>, EventIndex, PeerIndex>,
     // Creator's signature of `content`.
@@ -208,11 +212,17 @@ impl<P: PublicId> Event<P> {
         packed_event: PackedEvent<T, P>,
         ctx: EventContextRef<T, S>,
     ) -> Result<Option<UnpackedEvent<T, P>>, Error> {
-        let hash = compute_event_hash_and_verify_signature(
-            &packed_event.content,
-            &packed_event.signature,
-        )?;
+        let hash = packed_event.verify_signature()?;
+        Self::unpack_verified(packed_event, hash, ctx)
+    }
 
+    // Like `unpack`, but for an event whose signature has already been verified by
+    // `PackedEvent::verify_signature`, which returned `hash`.
+    pub fn unpack_verified<T: NetworkEvent, S: SecretId<PublicId = P>>(
+        packed_event: PackedEvent<T, P>,
+        hash: EventHash,
+        ctx: EventContextRef<T, S>,
+    ) -> Result<Option<UnpackedEvent<T, P>>, Error> {
         if ctx.graph.contains(&hash) {
             return Ok(None);
         }
@@ -355,6 +365,12 @@ impl<P: PublicId> Event<P> {
             .map(|(peer_index, info)| (peer_index, info.last))
     }
 
+    // Approximate number of bytes held by this event, including its cached ancestor info.
+    pub fn estimated_memory_usage(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.cache.ancestor_info.iter().count() * mem::size_of::<Option<AncestorInfo>>()
+    }
+
     pub fn last_ancestor_by(&self, creator: PeerIndex) -> Option<usize> {
         self.cache.ancestor_info.get(creator).map(|info| info.last)
     }
@@ -584,6 +600,7 @@ pub(crate) enum CauseInput {
 }
 
 // Properties of `Event` that can be computed from its `Content`.
+#[derive(Clone)]
 struct Cache {
     // Hash of `Event`s `Content`.
     hash: EventHash,
@@ -634,6 +651,9 @@ fn get_event<'a, P: PublicId>(
         .map(|event| event.inner())
 }
 
+// The index is derived from the self-parent, which must already be in our graph for the event to
+// be unpacked at all (otherwise we fail with `Error::UnknownSelfParent`). So a peer can't make us
+// accept an event skipping an index by creator: there are no gaps to detect, only forks.
 fn compute_index_by_creator<P: PublicId>(self_parent: Option<&Event<P>>) -> usize {
     if let Some(self_parent) = self_parent {
         self_parent.index_by_creator() + 1
@@ -654,7 +674,7 @@ fn compute_event_hash_and_signature<T: NetworkEvent, S: SecretId>(
     (hash, signature)
 }
 
-fn compute_event_hash_and_verify_signature<T: NetworkEvent, P: PublicId>(
+pub(super) fn compute_event_hash_and_verify_signature<T: NetworkEvent, P: PublicId>(
     content: &Content<Vote<T, P>, EventHash, P>,
     signature: &P::Signature,
 ) -> Result<EventHash, Error> {
@@ -739,14 +759,14 @@ mod tests {
         let mut context0 = EventContext::new(PeerId::new(id0));
         let mut context1 = EventContext::new(PeerId::new(id1));
 
-        let _ = context0.peer_list.add_peer(
+        let _ = unwrap!(context0.peer_list.add_peer(
             context1.peer_list.our_pub_id().clone(),
             PeerState::VOTE | PeerState::SEND | PeerState::RECV,
-        );
-        let _ = context1.peer_list.add_peer(
+        ));
+        let _ = unwrap!(context1.peer_list.add_peer(
             context0.peer_list.our_pub_id().clone(),
             PeerState::VOTE | PeerState::SEND | PeerState::RECV,
-        );
+        ));
 
         let event0 = Event::new_initial(context0.as_ref());
         let event1 = Event::new_initial(context1.as_ref());
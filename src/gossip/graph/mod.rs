@@ -20,9 +20,11 @@ use std::collections::{
     btree_map::{BTreeMap, Entry},
     BTreeSet,
 };
+#[cfg(feature = "timing")]
+use std::time::Instant;
 
 /// The gossip graph.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Graph<P: PublicId> {
     events: Vec<Event<P>>,
     indices: BTreeMap<EventHash, EventIndex>,
@@ -30,8 +32,27 @@ pub(crate) struct Graph<P: PublicId> {
     /// no associated descendant `Response`.
     #[cfg(feature = "malice-detection")]
     awaiting_associated_events: FnvHashSet<EventIndex>,
+    /// Times at which the events were inserted, in the same order as `events`.
+    #[cfg(feature = "timing")]
+    insertion_times: Vec<Instant>,
 }
 
+// The insertion times are local to this node, so they don't take part in equality.
+impl<P: PublicId> PartialEq for Graph<P> {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "malice-detection")]
+        {
+            if self.awaiting_associated_events != other.awaiting_associated_events {
+                return false;
+            }
+        }
+
+        self.events == other.events && self.indices == other.indices
+    }
+}
+
+impl<P: PublicId> Eq for Graph<P> {}
+
 impl<P: PublicId> Default for Graph<P> {
     fn default() -> Self {
         Self {
@@ -39,6 +60,8 @@ impl<P: PublicId> Default for Graph<P> {
             indices: BTreeMap::new(),
             #[cfg(feature = "malice-detection")]
             awaiting_associated_events: FnvHashSet::default(),
+            #[cfg(feature = "timing")]
+            insertion_times: Vec::new(),
         }
     }
 }
@@ -81,6 +104,9 @@ impl<P: PublicId> Graph<P> {
                 self.events.push(event);
                 let _ = entry.insert(index);
 
+                #[cfg(feature = "timing")]
+                self.insertion_times.push(Instant::now());
+
                 #[cfg(feature = "malice-detection")]
                 self.update_awaiting(index);
 
@@ -101,6 +127,12 @@ impl<P: PublicId> Graph<P> {
             .map(|event| IndexedEventRef { index, event })
     }
 
+    /// Gets the time the event with the given `index` was inserted, if it exists.
+    #[cfg(feature = "timing")]
+    pub fn insertion_time(&self, index: EventIndex) -> Option<Instant> {
+        self.insertion_times.get(index.0).cloned()
+    }
+
     /// Gets `Event` by the given `hash`, if it exists.
     pub fn get_by_hash<'a>(&'a self, hash: &EventHash) -> Option<IndexedEventRef<'a, P>> {
         self.get_index(hash).and_then(|index| self.get(index))
@@ -269,6 +301,8 @@ impl<P: PublicId> Graph<P> {
         }
         let event = self.events.pop()?;
         let _ = self.indices.remove(event.hash());
+        #[cfg(feature = "timing")]
+        let _ = self.insertion_times.pop();
         Some((index, event))
     }
 }
@@ -388,6 +422,8 @@ pub(crate) mod snapshot {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "timing")]
+    use super::Graph;
     use crate::dev_utils::parse_test_dot_file;
 
     #[test]
@@ -424,4 +460,17 @@ mod tests {
 
         assert_eq!(actual_indices, sorted_indices);
     }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn equality_ignores_insertion_times() {
+        let graph = parse_test_dot_file("carol.dot").graph;
+
+        let mut other = Graph::new();
+        for event in graph.iter() {
+            let _ = other.insert(event.inner().clone());
+        }
+
+        assert_eq!(other, graph);
+    }
 }
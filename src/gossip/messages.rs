@@ -6,18 +6,68 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{gossip::packed_event::PackedEvent, id::PublicId, network_event::NetworkEvent};
+use crate::{
+    gossip::{event_hash::EventHash, packed_event::PackedEvent},
+    id::PublicId,
+    network_event::NetworkEvent,
+};
+use std::collections::BTreeMap;
+
+/// Version of the gossip wire protocol spoken by this crate. It is sent as the first field of
+/// every `Request` and `Response`, so it can be decoded even if the rest of the message can't.
+pub const PROTOCOL_VERSION: u16 = 1;
+/// Oldest version of the gossip wire protocol this crate can still handle.
+pub const MIN_SUPPORTED_PROTOCOL_VERSION: u16 = 1;
 
 /// A gossip request message.
+///
+/// A `Request` can be cloned, e.g. to resend it after a transport failure, but it must only ever
+/// be sent to the peer it was created for: its last event is the `Requesting` event naming that
+/// peer, which makes the request invalid for any other recipient.
 #[serde(bound = "")]
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub struct Request<T: NetworkEvent, P: PublicId> {
+    // Must stay the first field, see `PROTOCOL_VERSION`.
+    pub(crate) protocol_version: u16,
     pub(crate) packed_events: Vec<PackedEvent<T, P>>,
 }
 
 impl<T: NetworkEvent, P: PublicId> Request<T, P> {
     pub(crate) fn new(packed_events: Vec<PackedEvent<T, P>>) -> Self {
-        Self { packed_events }
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            packed_events,
+        }
+    }
+
+    /// Returns the version of the gossip wire protocol this request was encoded with.
+    pub fn protocol_version(&self) -> u16 {
+        self.protocol_version
+    }
+
+    /// Returns the number of gossip events carried by this request.
+    pub fn len(&self) -> usize {
+        self.packed_events.len()
+    }
+
+    /// Returns `true` if this request carries no gossip events.
+    pub fn is_empty(&self) -> bool {
+        self.packed_events.is_empty()
+    }
+
+    /// Checks that the signature of each gossip event carried by this request matches the event's
+    /// content and creator, without needing any of the events' parents. Returns the hash of the
+    /// first event failing the check. This allows cheaply dropping forged messages, e.g. before
+    /// forwarding them; `Parsec::handle_request` performs the same check anyway.
+    pub fn verify_signatures(&self) -> Result<(), EventHash> {
+        match self
+            .packed_events
+            .iter()
+            .find(|packed_event| !packed_event.has_valid_signature())
+        {
+            Some(packed_event) => Err(packed_event.compute_hash()),
+            None => Ok(()),
+        }
     }
 }
 
@@ -25,11 +75,57 @@ impl<T: NetworkEvent, P: PublicId> Request<T, P> {
 #[serde(bound = "")]
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub struct Response<T: NetworkEvent, P: PublicId> {
+    // Must stay the first field, see `PROTOCOL_VERSION`.
+    pub(crate) protocol_version: u16,
     pub(crate) packed_events: Vec<PackedEvent<T, P>>,
 }
 
 impl<T: NetworkEvent, P: PublicId> Response<T, P> {
     pub(crate) fn new(packed_events: Vec<PackedEvent<T, P>>) -> Self {
-        Self { packed_events }
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            packed_events,
+        }
+    }
+
+    /// Returns the version of the gossip wire protocol this response was encoded with.
+    pub fn protocol_version(&self) -> u16 {
+        self.protocol_version
+    }
+
+    /// Returns the number of gossip events carried by this response.
+    pub fn len(&self) -> usize {
+        self.packed_events.len()
+    }
+
+    /// Returns `true` if this response carries no gossip events. Such a response would be rejected
+    /// by `Parsec::handle_response`, so there is no need to send it.
+    pub fn is_empty(&self) -> bool {
+        self.packed_events.is_empty()
+    }
+}
+
+/// A declaration of which gossip events the sender already holds, to be answered with a `Request`
+/// carrying only the events it lacks (see `Parsec::create_frontier_request`).
+#[serde(bound = "")]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct FrontierRequest<P: PublicId> {
+    // Must stay the first field, see `PROTOCOL_VERSION`.
+    pub(crate) protocol_version: u16,
+    // Index by creator of the latest event the sender holds from each peer.
+    pub(crate) frontier: BTreeMap<P, usize>,
+}
+
+impl<P: PublicId> FrontierRequest<P> {
+    pub(crate) fn new(frontier: BTreeMap<P, usize>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            frontier,
+        }
+    }
+
+    /// Returns the version of the gossip wire protocol this frontier request was encoded with.
+    pub fn protocol_version(&self) -> u16 {
+        self.protocol_version
     }
 }
@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod abstract_event;
+mod causal_relation;
 mod cause;
 mod content;
 mod event;
@@ -16,6 +17,7 @@ mod event_utils;
 mod graph;
 mod messages;
 mod packed_event;
+mod sync_stats;
 
 #[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
 pub(super) use self::cause::Cause;
@@ -30,7 +32,11 @@ pub(super) use self::{
     graph::{EventIndex, Graph, IndexedEventRef},
 };
 pub use self::{
+    causal_relation::CausalRelation,
     event_hash::EventHash,
-    messages::{Request, Response},
+    messages::{
+        FrontierRequest, Request, Response, MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION,
+    },
     packed_event::PackedEvent,
+    sync_stats::SyncStats,
 };
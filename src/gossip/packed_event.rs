@@ -6,7 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{content::Content, event_hash::EventHash};
+use super::{
+    content::Content, event::compute_event_hash_and_verify_signature, event_hash::EventHash,
+};
+use crate::{error::Error, hash::Hash, serialise, NetworkEvent, PublicId, Vote};
 #[cfg(all(feature = "mock", any(feature = "testing", test)))]
 use crate::{
     gossip::Cause,
@@ -14,7 +17,6 @@ use crate::{
     mock::{PeerId, Transaction},
     observation::Observation,
 };
-use crate::{hash::Hash, serialise, NetworkEvent, PublicId, Vote};
 use std::fmt::{self, Debug, Formatter};
 
 /// Packed event contains only content and signature.
@@ -42,6 +44,25 @@ impl<T: NetworkEvent, P: PublicId> PackedEvent<T, P> {
     pub(crate) fn compute_hash(&self) -> EventHash {
         EventHash(Hash::from(serialise(&self.content).as_slice()))
     }
+
+    // Returns whether the signature matches the content and its creator.
+    pub(crate) fn has_valid_signature(&self) -> bool {
+        self.verify_signature().is_ok()
+    }
+
+    // Returns the hash of this event if the signature matches the content and its creator, or
+    // `Error::SignatureFailure` otherwise.
+    pub(crate) fn verify_signature(&self) -> Result<EventHash, Error> {
+        compute_event_hash_and_verify_signature(&self.content, &self.signature)
+    }
+
+    // Returns the hashes of this event's parents.
+    pub(crate) fn parent_hashes(&self) -> impl Iterator<Item = &EventHash> {
+        self.content
+            .self_parent()
+            .into_iter()
+            .chain(self.content.other_parent())
+    }
 }
 
 #[cfg(all(feature = "mock", any(feature = "testing", test)))]
@@ -105,6 +126,12 @@ impl PackedEvent<Transaction, PeerId> {
         Self::new(content)
     }
 
+    /// Replaces the signature with one made by `signer`. Unless `signer` is the creator, the
+    /// result no longer passes signature verification.
+    pub fn resign(&mut self, signer: &PeerId) {
+        self.signature = signer.sign_detached(&serialise(&self.content));
+    }
+
     fn new(content: Content<Vote<Transaction, PeerId>, EventHash, PeerId>) -> Self {
         let serialised_content = serialise(&content);
         let signature = content.creator.sign_detached(&serialised_content);
@@ -0,0 +1,36 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+/// Accounting of what handling a single gossip `Request` or `Response` achieved.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SyncStats {
+    pub(crate) new_events: usize,
+    pub(crate) accusations_raised: usize,
+    pub(crate) blocks_consensused: usize,
+}
+
+impl SyncStats {
+    /// Returns the number of events carried by the message which we didn't know yet.
+    pub fn new_events(&self) -> usize {
+        self.new_events
+    }
+
+    /// Returns the number of accusations we raised for malice detected in the message. Accusations
+    /// left pending from earlier messages or approved via `approve_accusation` aren't counted,
+    /// even if they're raised while handling it. For a message we already handled, the accusations
+    /// are counted but only raised along with the next one. Always 0 unless the `malice-detection`
+    /// feature is enabled.
+    pub fn accusations_raised(&self) -> usize {
+        self.accusations_raised
+    }
+
+    /// Returns the number of blocks we consensused while handling the message.
+    pub fn blocks_consensused(&self) -> usize {
+        self.blocks_consensused
+    }
+}
@@ -11,17 +11,28 @@ use tiny_keccak;
 
 pub const HASH_LEN: usize = 32;
 
+/// SHA3-256 hash. Use `Hash::from(bytes)` to compute the hash of a byte slice.
+///
+/// Every hash in the protocol is computed through `Hash::from`: event hashes (which are also what
+/// event signatures cover), observation hashes and hence `ObservationId`s. They are exchanged
+/// between peers and compared for equality, so all nodes of a network must use the same hash
+/// function; a node built with a different one can't process anyone else's gossip, since every
+/// parent reference it receives would be unknown to it. Changing the function is therefore a
+/// network-wide breaking change, like a change to the wire format.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Hash([u8; HASH_LEN]);
 
 impl Hash {
+    /// Hash consisting of all zeros.
     pub const ZERO: Self = Hash([0; HASH_LEN]);
 
+    /// Creates a `Hash` from its raw bytes.
     #[cfg(any(test, feature = "testing"))]
     pub fn from_bytes(bytes: [u8; HASH_LEN]) -> Self {
         Hash(bytes)
     }
 
+    /// Returns the raw bytes of this hash.
     #[cfg(feature = "mock")]
     pub fn as_bytes(&self) -> &[u8; HASH_LEN] {
         &self.0
@@ -30,10 +41,16 @@ impl Hash {
 
 impl<'a> From<&'a [u8]> for Hash {
     fn from(src: &'a [u8]) -> Self {
-        Hash(tiny_keccak::sha3_256(src))
+        Hash(digest(src))
     }
 }
 
+// The hash function used throughout the crate. This is the only place which needs to change to
+// use a different one, as long as it produces `HASH_LEN` bytes.
+fn digest(src: &[u8]) -> [u8; HASH_LEN] {
+    tiny_keccak::sha3_256(src)
+}
+
 impl Debug for Hash {
     #[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
@@ -20,6 +20,11 @@ pub trait PublicId: Clone + Eq + Ord + Hash + Serialize + DeserializeOwned + Deb
 
 /// The secret identity of a node.  It provides functionality to allow it to be used as an
 /// asymmetric signing secret key and to also yield the associated public identity.
+///
+/// `Parsec` only ever signs through `sign_detached` and decrypts DKG messages through `decrypt`, so
+/// the key material doesn't need to live in the same process: an implementation may hold just the
+/// public identity and forward these calls to an external signer. Both are called synchronously
+/// while handling gossip, so such a signer should answer promptly.
 pub trait SecretId {
     /// The associated public identity type.
     type PublicId: PublicId;
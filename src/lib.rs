@@ -184,6 +184,7 @@ mod gossip;
 mod hash;
 mod id;
 mod key_gen;
+mod memory_report;
 mod meta_voting;
 mod network_event;
 mod observation;
@@ -206,15 +207,26 @@ pub mod mock;
 
 #[cfg(feature = "dump-graphs")]
 pub use crate::dump_graph::{DumpGraphMode, DIR, DUMP_MODE};
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+pub use crate::meta_voting::{MetaVoteSnapshot, MetaVoteState};
+#[cfg(any(test, feature = "testing"))]
+pub use crate::parsec::gossip_until_stable;
 pub use crate::{
     block::Block,
-    error::{Error, Result},
-    gossip::{EventHash, PackedEvent, Request, Response},
+    error::{Error, ErrorKind, GraphInconsistency, Result},
+    gossip::{
+        CausalRelation, EventHash, FrontierRequest, PackedEvent, Request, Response, SyncStats,
+        MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION,
+    },
+    hash::Hash,
     id::{Proof, PublicId, SecretId},
     key_gen::dkg_result::*,
+    memory_report::MemoryReport,
+    meta_voting::{ConsensusConfidence, MetaEventInfo},
     network_event::NetworkEvent,
-    observation::{ConsensusMode, Malice, Observation},
+    observation::{ConsensusMode, Malice, MaliceKind, Observation, ObservationId},
     parsec::Parsec,
+    peer_list::{MembershipChange, PeerList, PeerState, ResponsivenessStats},
     vote::Vote,
 };
 
@@ -0,0 +1,46 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+/// Approximate breakdown, in bytes, of the memory held by the main structures of a `Parsec`
+/// instance. The figures are estimated from the number of entries in each structure and the sizes
+/// of their types, so they don't account for memory owned by the payloads themselves.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct MemoryReport {
+    pub(crate) graph: usize,
+    pub(crate) observations: usize,
+    pub(crate) meta_election: usize,
+    pub(crate) consensused_blocks: usize,
+}
+
+impl MemoryReport {
+    /// Returns the estimated size of the gossip graph, including the ancestor info cached for each
+    /// event.
+    pub fn graph(&self) -> usize {
+        self.graph
+    }
+
+    /// Returns the estimated size of the store of observations voted for in the gossip graph.
+    pub fn observations(&self) -> usize {
+        self.observations
+    }
+
+    /// Returns the estimated size of the meta-events and meta-votes of the current meta-election.
+    pub fn meta_election(&self) -> usize {
+        self.meta_election
+    }
+
+    /// Returns the estimated size of the consensused blocks which haven't been polled yet.
+    pub fn consensused_blocks(&self) -> usize {
+        self.consensused_blocks
+    }
+
+    /// Returns the sum of all the estimates.
+    pub fn total(&self) -> usize {
+        self.graph + self.observations + self.meta_election + self.consensused_blocks
+    }
+}
@@ -11,13 +11,14 @@ use super::{
     meta_vote::MetaVote,
 };
 use crate::{
+    error::Error,
     gossip::{EventIndex, Graph},
     id::PublicId,
-    observation::ObservationKey,
+    observation::{ObservationHash, ObservationKey},
     peer_list::{PeerIndex, PeerIndexMap, PeerIndexSet, PeerListChange},
 };
 use fnv::{FnvHashMap, FnvHashSet};
-use std::{cmp, collections::BTreeSet, usize};
+use std::{cmp, collections::BTreeSet, mem, usize};
 
 lazy_static! {
     static ref EMPTY_BTREESET_EVENT_INDEX: BTreeSet<EventIndex> = BTreeSet::new();
@@ -43,8 +44,21 @@ pub(crate) struct MetaElection {
     pub(crate) interesting_events: PeerIndexMap<(Vec<EventIndex>, FnvHashSet<ObservationKey>)>,
     // All events that carry a payload that hasn't yet been consensused.
     pub(crate) unconsensused_events: UnconsensusedEvents,
+    // Payloads manually abandoned via `Parsec::abandon_unconsensused` during the current
+    // meta-election. Events carrying them are no longer tracked as unconsensused, until the next
+    // meta-election starts.
+    pub(crate) abandoned_payloads: FnvHashSet<ObservationHash>,
     // Keys of the consensused blocks' payloads in the order they were consensused.
     pub(crate) consensus_history: Vec<ObservationKey>,
+    // Number of entries removed from the front of `consensus_history` by `trim_history`. Positions
+    // in the history, including those in `voters_history`, count them too.
+    pub(crate) trimmed_history_len: usize,
+    // Voter sets of the past and current meta-elections, each with the position in
+    // `consensus_history` of the first payload consensused while it was in effect.
+    pub(crate) voters_history: Vec<(usize, PeerIndexSet)>,
+    // Peers removed by consensus, with the key of the payload which removed them and the voters of
+    // the meta-election which decided on it.
+    pub(crate) removals: PeerIndexMap<(ObservationKey, PeerIndexSet)>,
     // Topological index of the first unconsensused payload-carrying event or of the first observer
     // event, whichever is the greater.
     pub(crate) continue_consensus_start_index: usize,
@@ -56,10 +70,14 @@ impl MetaElection {
     pub fn new(voters: PeerIndexSet) -> Self {
         MetaElection {
             meta_events: FnvHashMap::default(),
+            voters_history: vec![(0, voters.clone())],
             voters,
+            removals: PeerIndexMap::default(),
             interesting_events: PeerIndexMap::default(),
             unconsensused_events: UnconsensusedEvents::default(),
+            abandoned_payloads: FnvHashSet::default(),
             consensus_history: Vec::new(),
+            trimmed_history_len: 0,
             continue_consensus_start_index: 0,
             new_consensus_start_index: 0,
         }
@@ -98,15 +116,83 @@ impl MetaElection {
             .filter(|meta_votes| !meta_votes.is_empty())
     }
 
+    /// Indices of the observer events of the current meta-election, in topological order.
+    pub fn observers(&self) -> Vec<EventIndex> {
+        let mut observers: Vec<_> = self
+            .meta_events
+            .iter()
+            .filter(|(_, meta_event)| meta_event.is_observer())
+            .map(|(event_index, _)| *event_index)
+            .collect();
+        observers.sort();
+        observers
+    }
+
     /// List of voters participating in the current meta-election.
     pub fn voters(&self) -> &PeerIndexSet {
         &self.voters
     }
 
+    /// The retained part of the consensus history, starting at position `trimmed_history_len`.
     pub fn consensus_history(&self) -> &[ObservationKey] {
         &self.consensus_history
     }
 
+    /// Total number of payloads consensused so far, including the trimmed ones.
+    pub fn consensus_history_len(&self) -> usize {
+        self.trimmed_history_len + self.consensus_history.len()
+    }
+
+    /// Removes all but the last `keep_last` entries of the consensus history. Returns the number
+    /// of entries removed.
+    pub fn trim_history(&mut self, keep_last: usize) -> usize {
+        let num_trimmed = self.consensus_history.len().saturating_sub(keep_last);
+        let _ = self.consensus_history.drain(..num_trimmed);
+        self.trimmed_history_len += num_trimmed;
+
+        // Drop the voter sets which were only in effect for trimmed entries.
+        let num_obsolete = self
+            .voters_history
+            .iter()
+            .skip(1)
+            .take_while(|(start, _)| *start <= self.trimmed_history_len)
+            .count();
+        let _ = self.voters_history.drain(..num_obsolete);
+
+        num_trimmed
+    }
+
+    /// Voters of the meta-election which consensused the payload at the given position in the
+    /// consensus history, or `None` if there's no such position or it has been trimmed.
+    pub fn voters_at(&self, history_index: usize) -> Option<&PeerIndexSet> {
+        if history_index < self.trimmed_history_len || history_index >= self.consensus_history_len()
+        {
+            return None;
+        }
+        let position = match self
+            .voters_history
+            .binary_search_by_key(&history_index, |(start, _)| *start)
+        {
+            Ok(position) => position,
+            Err(position) => position.checked_sub(1)?,
+        };
+        self.voters_history.get(position).map(|(_, voters)| voters)
+    }
+
+    /// Records that the current meta-election decided on removing the given peer, by consensus on
+    /// the payload with the given key.
+    pub fn record_removal(&mut self, peer_index: PeerIndex, payload_key: ObservationKey) {
+        let _ = self
+            .removals
+            .insert(peer_index, (payload_key, self.voters.clone()));
+    }
+
+    /// The key of the payload which removed the given peer and the voters of the meta-election
+    /// which decided on it, or `None` if the peer hasn't been removed by consensus.
+    pub fn removal(&self, peer_index: PeerIndex) -> Option<&(ObservationKey, PeerIndexSet)> {
+        self.removals.get(peer_index)
+    }
+
     pub fn interesting_events(&self) -> impl Iterator<Item = (PeerIndex, &[EventIndex])> {
         self.interesting_events
             .iter()
@@ -131,27 +217,58 @@ impl MetaElection {
             .map_or(false, |(_, contents)| contents.contains(payload_key))
     }
 
+    // Returns whether any peer's interesting content in the current election includes a payload
+    // with the given hash.
+    pub fn is_interesting_payload(&self, payload_hash: &ObservationHash) -> bool {
+        self.interesting_events.iter().any(|(_, (_, contents))| {
+            contents
+                .iter()
+                .any(|payload_key| payload_key.hash() == payload_hash)
+        })
+    }
+
     pub fn continue_consensus_start_index(&self) -> usize {
         self.continue_consensus_start_index
     }
 
-    /// Starts new election.
+    pub fn new_consensus_start_index(&self) -> usize {
+        self.new_consensus_start_index
+    }
+
+    /// Discards all meta-events of the current election, so they get recreated from scratch.
+    /// Returns the topological index from which the events need to be reprocessed.
+    pub fn reset_meta_events(&mut self) -> usize {
+        self.meta_events.clear();
+        self.interesting_events.clear();
+        self.continue_consensus_start_index = self.new_consensus_start_index;
+        self.new_consensus_start_index
+    }
+
+    /// Starts new election. The election is started even if some of the peer list changes are
+    /// inconsistent with the current voters, but `Error::Logic` is returned in that case.
     pub fn new_election<P: PublicId>(
         &mut self,
         graph: &Graph<P>,
         decided_keys: Vec<ObservationKey>,
         peer_list_changes: Vec<PeerListChange>,
-    ) {
+    ) -> Result<(), Error> {
         let peer_list_changed = !peer_list_changes.is_empty();
+        let next_history_index = self.consensus_history_len() + decided_keys.len();
 
-        self.update_voters(peer_list_changes);
+        let result = self.update_voters(peer_list_changes);
+        if peer_list_changed {
+            self.voters_history
+                .push((next_history_index, self.voters.clone()));
+        }
         self.update_unconsensused_events(&decided_keys);
+        self.abandoned_payloads.clear();
         self.update_new_consensus_start_index(graph.len());
         self.update_continue_consensus_start_index(peer_list_changed);
         self.update_meta_events(&decided_keys, peer_list_changed);
         self.update_interesting_content(graph);
 
         self.consensus_history.extend(decided_keys);
+        result
     }
 
     #[cfg(feature = "dump-graphs")]
@@ -159,7 +276,31 @@ impl MetaElection {
         &self.meta_events
     }
 
+    // Approximate number of bytes held by the meta-events of this election.
+    pub fn estimated_memory_usage(&self) -> usize {
+        self.meta_events
+            .values()
+            .map(|meta_event| {
+                let meta_votes: usize = meta_event
+                    .meta_votes
+                    .iter()
+                    .map(|(_, votes)| {
+                        mem::size_of::<Option<Vec<MetaVote>>>()
+                            + votes.len() * mem::size_of::<MetaVote>()
+                    })
+                    .sum();
+                mem::size_of::<(EventIndex, MetaEvent)>()
+                    + meta_event.interesting_content.len() * mem::size_of::<ObservationKey>()
+                    + meta_votes
+            })
+            .sum()
+    }
+
     pub fn add_unconsensused_event(&mut self, event_index: EventIndex, key: ObservationKey) {
+        if self.abandoned_payloads.contains(key.hash()) {
+            return;
+        }
+
         let _ = self
             .unconsensused_events
             .ordered_indices
@@ -188,6 +329,27 @@ impl MetaElection {
         indices.iter().cloned()
     }
 
+    /// Stops tracking the events carrying the payload with the given hash as unconsensused, now and
+    /// for the rest of the current election, and moves the start of the next election past them if
+    /// possible. Returns `false` if no such events are currently tracked.
+    pub fn abandon_payload(&mut self, hash: ObservationHash, graph_len: usize) -> bool {
+        let keys: Vec<_> = self
+            .unconsensused_events
+            .indices_by_key
+            .keys()
+            .filter(|key| *key.hash() == hash)
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            return false;
+        }
+
+        let _ = self.abandoned_payloads.insert(hash);
+        self.update_unconsensused_events(&keys);
+        self.update_new_consensus_start_index(graph_len);
+        true
+    }
+
     fn add_interesting_event(
         &mut self,
         creator: PeerIndex,
@@ -216,21 +378,25 @@ impl MetaElection {
         }
     }
 
-    fn update_voters(&mut self, peer_list_changes: Vec<PeerListChange>) {
+    fn update_voters(&mut self, peer_list_changes: Vec<PeerListChange>) -> Result<(), Error> {
+        let mut result = Ok(());
         for peer_list_change in peer_list_changes {
             match peer_list_change {
                 PeerListChange::Add(peer_index) => {
                     if !self.voters.insert(peer_index) {
-                        log_or_panic!("Meta election already contains {:?}", peer_index);
+                        error!("Meta election already contains {:?}", peer_index);
+                        result = Err(Error::Logic);
                     }
                 }
                 PeerListChange::Remove(peer_index) => {
                     if !self.voters.remove(peer_index) {
-                        log_or_panic!("Meta election doesn't contain {:?}", peer_index);
+                        error!("Meta election doesn't contain {:?}", peer_index);
+                        result = Err(Error::Logic);
                     }
                 }
             }
         }
+        result
     }
 
     fn update_new_consensus_start_index(&mut self, graph_len: usize) {
@@ -297,6 +463,42 @@ impl MetaElection {
     }
 }
 
+/// Read-only summary of how close a payload is to consensus in the current meta-election.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ConsensusConfidence {
+    num_voters: usize,
+    num_interesting: usize,
+    num_decided: usize,
+}
+
+impl ConsensusConfidence {
+    pub(crate) fn new(num_voters: usize, num_interesting: usize, num_decided: usize) -> Self {
+        Self {
+            num_voters,
+            num_interesting,
+            num_decided,
+        }
+    }
+
+    /// Returns the number of voters in the current meta-election.
+    pub fn num_voters(&self) -> usize {
+        self.num_voters
+    }
+
+    /// Returns the number of voters whose first interesting event carries the payload in its
+    /// interesting content.
+    pub fn num_interesting(&self) -> usize {
+        self.num_interesting
+    }
+
+    /// Returns the number of those voters whose meta-vote, as seen by our latest event, is
+    /// already decided as `true`. Once every voter's meta-vote is decided, the payloads in the
+    /// interesting content of these voters are consensused.
+    pub fn num_decided(&self) -> usize {
+        self.num_decided
+    }
+}
+
 #[cfg(any(all(test, feature = "mock"), feature = "dump-graphs"))]
 pub(crate) mod snapshot {
     use super::{super::meta_event::snapshot::MetaEventSnapshot, *};
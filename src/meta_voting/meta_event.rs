@@ -10,7 +10,8 @@ use super::meta_vote::MetaVote;
 use crate::{
     gossip::IndexedEventRef,
     id::PublicId,
-    observation::ObservationKey,
+    network_event::NetworkEvent,
+    observation::{Observation, ObservationKey},
     peer_list::{PeerIndex, PeerIndexMap, PeerIndexSet},
 };
 
@@ -97,6 +98,47 @@ impl MetaEvent {
     }
 }
 
+/// Read-only information about the meta-event of a gossip event in the current meta-election.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MetaEventInfo<T: NetworkEvent, P: PublicId> {
+    is_observer: bool,
+    num_observees: usize,
+    interesting_content: Vec<Observation<T, P>>,
+}
+
+impl<T: NetworkEvent, P: PublicId> MetaEventInfo<T, P> {
+    pub(crate) fn new(meta_event: &MetaEvent, interesting_content: Vec<Observation<T, P>>) -> Self {
+        let num_observees = match meta_event.observer {
+            Observer::This(ref observees) => observees.len(),
+            Observer::Ancestor | Observer::None => 0,
+        };
+
+        Self {
+            is_observer: meta_event.is_observer(),
+            num_observees,
+            interesting_content,
+        }
+    }
+
+    /// Is the event an observer, i.e. the first event of its creator to strongly see a
+    /// supermajority of the voters?
+    pub fn is_observer(&self) -> bool {
+        self.is_observer
+    }
+
+    /// Returns the number of peers the event strongly sees, if it's an observer. Otherwise
+    /// returns 0.
+    pub fn num_observees(&self) -> usize {
+        self.num_observees
+    }
+
+    /// Returns the observations the event deems interesting, in the order the event's creator
+    /// would like them consensused.
+    pub fn interesting_content(&self) -> &[Observation<T, P>] {
+        &self.interesting_content
+    }
+}
+
 pub(crate) struct MetaEventBuilder<'a, P: PublicId + 'a> {
     event: IndexedEventRef<'a, P>,
     meta_event: MetaEvent,
@@ -15,6 +15,8 @@ use super::{
     meta_vote_counts::MetaVoteCounts,
     meta_vote_values::{MetaVoteValues, Step},
 };
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+use crate::{gossip::EventHash, id::PublicId};
 use std::{
     collections::BTreeMap,
     fmt::{self, Debug, Formatter},
@@ -186,6 +188,56 @@ impl MetaVote {
     }
 }
 
+/// The meta-votes of the current meta-election, as exported by `Parsec::export_meta_votes`. It can
+/// be serialised, e.g. to JSON, so that the snapshots taken by different peers can be diffed to
+/// find where their views diverged.
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+#[serde(bound = "")]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct MetaVoteSnapshot<P: PublicId> {
+    /// The meta-votes of each event about each voter, in the order they were cast, keyed by the
+    /// hash of the event and the ID of the voter.
+    pub meta_votes: BTreeMap<EventHash, BTreeMap<P, Vec<MetaVoteState>>>,
+}
+
+/// A single meta-vote, as found in a `MetaVoteSnapshot`.
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct MetaVoteState {
+    /// The round of the meta-vote.
+    pub round: usize,
+    /// The step within the round: 0 for forced true, 1 for forced false and 2 for genuine flip.
+    pub step: u8,
+    /// The estimates, `true` first.
+    pub estimates: Vec<bool>,
+    /// The binary values, `true` first.
+    pub bin_values: Vec<bool>,
+    /// The auxiliary value, if any.
+    pub aux_value: Option<bool>,
+    /// The decision, if any.
+    pub decision: Option<bool>,
+}
+
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+impl MetaVoteState {
+    pub(crate) fn new(meta_vote: &MetaVote) -> Self {
+        let step = match meta_vote.step {
+            Step::ForcedTrue => 0,
+            Step::ForcedFalse => 1,
+            Step::GenuineFlip => 2,
+        };
+        let (estimates, bin_values, aux_value, decision) = meta_vote.values.to_bools();
+        Self {
+            round: meta_vote.round,
+            step,
+            estimates,
+            bin_values,
+            aux_value,
+            decision,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,6 +332,28 @@ impl MetaVoteValues {
         }
     }
 
+    // Returns the estimates, binary values, auxiliary value and decision.
+    #[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+    pub fn to_bools(self) -> (Vec<bool>, Vec<bool>, Option<bool>, Option<bool>) {
+        let bool_set_to_vec = |s: BoolSet| -> Vec<bool> {
+            [true, false]
+                .iter()
+                .cloned()
+                .filter(|b| s.contains(*b))
+                .collect()
+        };
+
+        match self {
+            MetaVoteValues::Decided(value) => (vec![value], vec![value], Some(value), Some(value)),
+            MetaVoteValues::Undecided(values) => (
+                bool_set_to_vec(values.estimates.0),
+                bool_set_to_vec(values.bin_values.0),
+                values.aux_value.0,
+                None,
+            ),
+        }
+    }
+
     #[cfg(feature = "dump-graphs")]
     pub fn as_chars(self) -> (char, char, char, char) {
         let pretty_bool = |b: bool| {
@@ -17,8 +17,11 @@ mod meta_vote_values;
 pub(crate) use self::meta_election::snapshot::MetaElectionSnapshot;
 #[cfg(any(test, feature = "testing"))]
 pub(crate) use self::meta_election::UnconsensusedEvents;
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+pub use self::meta_vote::{MetaVoteSnapshot, MetaVoteState};
 #[cfg(any(test, feature = "testing"))]
 pub(crate) use self::{bool_set::BoolSet, meta_vote_values::Step};
+pub use self::{meta_election::ConsensusConfidence, meta_event::MetaEventInfo};
 pub(crate) use self::{
     meta_election::MetaElection,
     meta_event::{MetaEvent, MetaEventBuilder, Observer},
@@ -25,9 +25,11 @@ pub const NAMES: &[&str] = &[
 ];
 
 lazy_static! {
+    // With the `mock` feature, the keypairs are derived from the names, so that simulations are
+    // reproducible given a seed.
     static ref PEERS: Vec<PeerId> = NAMES
         .iter()
-        .map(|name| PeerId::new_with_random_keypair(name))
+        .map(|name| PeerId::new_with_keypair(name))
         .collect();
 }
 
@@ -52,6 +54,8 @@ pub struct PeerId {
 }
 
 impl PeerId {
+    /// Returns a `PeerId` with the given name. With the `mock` feature, its keypair is derived
+    /// from the name, so the same name always yields the same `PeerId`.
     pub fn new(id: &str) -> Self {
         PEERS
             .iter()
@@ -223,7 +227,12 @@ impl Transaction {
     }
 }
 
-impl NetworkEvent for Transaction {}
+impl NetworkEvent for Transaction {
+    // An empty transaction is considered invalid, so tests can exercise payload validation.
+    fn validate(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
 
 impl Display for Transaction {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
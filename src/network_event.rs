@@ -6,6 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::hash::Hash;
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
@@ -14,4 +15,37 @@ use std::fmt::Debug;
 pub trait NetworkEvent:
     Clone + Eq + Ord + PartialEq + PartialOrd + Serialize + DeserializeOwned + Debug
 {
+    /// Returns the hash identifying this event when voted for as an `OpaquePayload`, or `None` to
+    /// identify it by its serialised form, which is the default.
+    ///
+    /// Override this if logically equal events can serialise differently, so that votes for them
+    /// count towards the same payload. Such events must also compare equal. Votes for them sign
+    /// over the canonical hash rather than the serialised event, so the resulting block holds the
+    /// proofs of all the votes, and they verify against any of the logically equal events.
+    fn canonical_hash(&self) -> Option<Hash> {
+        None
+    }
+
+    /// Returns whether this event is valid according to the rules of the application, e.g. whether
+    /// a transaction is properly signed. Defaults to `true`.
+    ///
+    /// We refuse to vote for an invalid event and we reject gossip carrying votes for invalid
+    /// events, so such events never get consensused. If the `malice-detection` feature is enabled,
+    /// the peers who created such votes are also accused of malice.
+    ///
+    /// This must be a pure function of the event, returning the same result on every peer and at
+    /// any time, e.g. it must not depend on the local clock or on the application's current state.
+    /// Otherwise peers disagree on which votes are valid, and honest peers are accused of malice.
+    fn validate(&self) -> bool {
+        true
+    }
+
+    /// Returns the priority of this event. Defaults to 0.
+    ///
+    /// Among the payloads which become candidates for consensus at the same time, those with a
+    /// higher priority are consensused first. As the priority is derived from the event itself,
+    /// all peers agree on it. It must therefore only depend on the event's content.
+    fn priority(&self) -> u8 {
+        0
+    }
 }
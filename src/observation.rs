@@ -21,6 +21,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     error::Error,
     fmt::{self, Debug, Formatter},
+    slice,
 };
 
 /// An enum of the various network events for which a peer can vote.
@@ -78,18 +79,52 @@ pub enum Observation<T: NetworkEvent, P: PublicId> {
     /// Vote for the next message (Part or Ack) to be handled for the Distributed Key Generation
     /// algorithm used by our common coin.
     DkgMessage(DkgMessage),
+    /// Vote for multiple events which are opaque to Parsec. The whole batch reaches consensus as
+    /// a single unit, yielding a single `Block`, and the order of the events is preserved.
+    Batch(Vec<T>),
+    /// Heartbeat vote without any payload, to keep meta-elections progressing when there is
+    /// nothing else to vote for. It reaches consensus like any other observation, but no `Block`
+    /// is created for it, so `poll()` never returns it.
+    /// The number distinguishes successive heartbeats of the same peer, so each one needs a fresh
+    /// number (e.g. from a counter). Heartbeats of different peers are consensused independently
+    /// of each other, as if in `ConsensusMode::Single`.
+    NoOp(u64),
 }
 
 impl<T: NetworkEvent, P: PublicId> Observation<T, P> {
     /// Is this observation's payload opaque to PARSEC?
     pub fn is_opaque(&self) -> bool {
-        if let Observation::OpaquePayload(_) = *self {
-            true
-        } else {
-            false
+        match *self {
+            Observation::OpaquePayload(_) | Observation::Batch(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the opaque payloads carried by this observation, in order.
+    pub fn opaque_payloads(&self) -> &[T] {
+        match *self {
+            Observation::OpaquePayload(ref payload) => slice::from_ref(payload),
+            Observation::Batch(ref payloads) => payloads,
+            _ => &[],
         }
     }
 
+    /// Returns the priority of this observation: the highest `NetworkEvent::priority` of its opaque
+    /// payloads, or 0 if it has none.
+    pub fn priority(&self) -> u8 {
+        self.opaque_payloads()
+            .iter()
+            .map(NetworkEvent::priority)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Number of bytes of this observation in its serialised form, as sent over the wire and, for
+    // non-opaque observations, hashed into its `ObservationHash`.
+    pub(crate) fn serialised_size(&self) -> usize {
+        serialise(self).len()
+    }
+
     /// Is this observation an internal `DkgMessage`
     pub fn is_dkg_message(&self) -> bool {
         match *self {
@@ -98,10 +133,18 @@ impl<T: NetworkEvent, P: PublicId> Observation<T, P> {
         }
     }
 
+    /// Is this observation a `NoOp` heartbeat
+    pub fn is_no_op(&self) -> bool {
+        match *self {
+            Observation::NoOp(_) => true,
+            _ => false,
+        }
+    }
+
     /// Is this observation an internal and should not be published in a `Block`
     pub fn is_internal(&self) -> bool {
         match *self {
-            Observation::DkgMessage(_) | Observation::StartDkg(_) => true,
+            Observation::DkgMessage(_) | Observation::StartDkg(_) | Observation::NoOp(_) => true,
             _ => false,
         }
     }
@@ -133,6 +176,8 @@ impl<T: NetworkEvent, P: PublicId> Debug for Observation<T, P> {
             Observation::OpaquePayload(payload) => {
                 write!(formatter, "OpaquePayload({:?})", payload)
             }
+            Observation::Batch(payloads) => write!(formatter, "Batch({:?})", payloads),
+            Observation::NoOp(number) => write!(formatter, "NoOp({})", number),
         }
     }
 }
@@ -165,10 +210,72 @@ pub enum Malice<T: NetworkEvent, P: PublicId> {
     InvalidRequest(Box<PackedEvent<T, P>>),
     /// The event should be a response to a request made to the peer, but isn't.
     InvalidResponse(Box<PackedEvent<T, P>>),
+    /// Event carries a vote for an opaque payload which fails `NetworkEvent::validate`. The
+    /// accusation contains the original event so other peers can verify the accusation directly.
+    InvalidPayload(Box<PackedEvent<T, P>>),
     /// Detectable but unprovable malice. Relies on consensus.
     Unprovable(UnprovableMalice),
     /// A node is not reporting malice when it should.
     Accomplice(EventHash, Box<Malice<T, P>>),
+    /// Two `Requesting` events with the same self-parent by the same creator, naming different
+    /// recipients. The hashes are in ascending order. Only the first such pair seen is accused
+    /// among `Requesting` events sharing a self-parent.
+    ForkedRequesting(EventHash, EventHash),
+}
+
+/// Type of malicious behaviour, without the evidence carried by the corresponding `Malice`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum MaliceKind {
+    /// See `Malice::UnexpectedGenesis`.
+    UnexpectedGenesis,
+    /// See `Malice::DuplicateVote`.
+    DuplicateVote,
+    /// See `Malice::MissingGenesis`.
+    MissingGenesis,
+    /// See `Malice::IncorrectGenesis`.
+    IncorrectGenesis,
+    /// See `Malice::Fork`.
+    Fork,
+    /// See `Malice::InvalidAccusation`.
+    InvalidAccusation,
+    /// See `Malice::OtherParentBySameCreator`.
+    OtherParentBySameCreator,
+    /// See `Malice::SelfParentByDifferentCreator`.
+    SelfParentByDifferentCreator,
+    /// See `Malice::InvalidRequest`.
+    InvalidRequest,
+    /// See `Malice::InvalidResponse`.
+    InvalidResponse,
+    /// See `Malice::InvalidPayload`.
+    InvalidPayload,
+    /// See `Malice::Unprovable`.
+    Unprovable,
+    /// See `Malice::Accomplice`.
+    Accomplice,
+    /// See `Malice::ForkedRequesting`.
+    ForkedRequesting,
+}
+
+impl<T: NetworkEvent, P: PublicId> Malice<T, P> {
+    /// Returns the type of this malice.
+    pub fn kind(&self) -> MaliceKind {
+        match self {
+            Malice::UnexpectedGenesis(_) => MaliceKind::UnexpectedGenesis,
+            Malice::DuplicateVote(_, _) => MaliceKind::DuplicateVote,
+            Malice::MissingGenesis(_) => MaliceKind::MissingGenesis,
+            Malice::IncorrectGenesis(_) => MaliceKind::IncorrectGenesis,
+            Malice::Fork(_) => MaliceKind::Fork,
+            Malice::InvalidAccusation(_) => MaliceKind::InvalidAccusation,
+            Malice::OtherParentBySameCreator(_) => MaliceKind::OtherParentBySameCreator,
+            Malice::SelfParentByDifferentCreator(_) => MaliceKind::SelfParentByDifferentCreator,
+            Malice::InvalidRequest(_) => MaliceKind::InvalidRequest,
+            Malice::InvalidResponse(_) => MaliceKind::InvalidResponse,
+            Malice::InvalidPayload(_) => MaliceKind::InvalidPayload,
+            Malice::Unprovable(_) => MaliceKind::Unprovable,
+            Malice::Accomplice(_, _) => MaliceKind::Accomplice,
+            Malice::ForkedRequesting(_, _) => MaliceKind::ForkedRequesting,
+        }
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -196,11 +303,33 @@ impl<T: NetworkEvent, P: PublicId> Malice<T, P> {
             | Malice::InvalidAccusation(hash)
             | Malice::Accomplice(hash, _) => Some(hash),
             Malice::DuplicateVote(_, _)
+            | Malice::ForkedRequesting(_, _)
             | Malice::IncorrectGenesis(_)
             | Malice::OtherParentBySameCreator(_)
             | Malice::SelfParentByDifferentCreator(_)
             | Malice::InvalidRequest(_)
             | Malice::InvalidResponse(_)
+            | Malice::InvalidPayload(_)
+            | Malice::Unprovable(_) => None,
+        }
+    }
+
+    // If the malice embeds the offending event itself, return it.
+    pub(crate) fn embedded_packed_event(&self) -> Option<&PackedEvent<T, P>> {
+        match self {
+            Malice::IncorrectGenesis(packed_event)
+            | Malice::OtherParentBySameCreator(packed_event)
+            | Malice::SelfParentByDifferentCreator(packed_event)
+            | Malice::InvalidRequest(packed_event)
+            | Malice::InvalidResponse(packed_event)
+            | Malice::InvalidPayload(packed_event) => Some(packed_event),
+            Malice::Accomplice(_, malice) => malice.embedded_packed_event(),
+            Malice::UnexpectedGenesis(_)
+            | Malice::MissingGenesis(_)
+            | Malice::Fork(_)
+            | Malice::InvalidAccusation(_)
+            | Malice::DuplicateVote(_, _)
+            | Malice::ForkedRequesting(_, _)
             | Malice::Unprovable(_) => None,
         }
     }
@@ -212,12 +341,15 @@ impl<T: NetworkEvent, P: PublicId> Malice<T, P> {
             | Malice::Fork(hash)
             | Malice::InvalidAccusation(hash)
             | Malice::Accomplice(hash, _) => vec![hash],
-            Malice::DuplicateVote(first, second) => vec![first, second],
+            Malice::DuplicateVote(first, second) | Malice::ForkedRequesting(first, second) => {
+                vec![first, second]
+            }
             Malice::IncorrectGenesis(_)
             | Malice::OtherParentBySameCreator(_)
             | Malice::SelfParentByDifferentCreator(_)
             | Malice::InvalidRequest(_)
             | Malice::InvalidResponse(_)
+            | Malice::InvalidPayload(_)
             | Malice::Unprovable(_) => vec![],
         }
     }
@@ -283,11 +415,38 @@ pub(crate) struct ObservationHash(pub(crate) Hash);
 
 impl ObservationHash {
     pub const ZERO: Self = ObservationHash(Hash::ZERO);
+
+    // Returns the hash of the observation derived from the canonical hashes of its payloads, or
+    // `None` if it isn't identified by those (see `NetworkEvent::canonical_hash`).
+    pub(crate) fn canonical<T: NetworkEvent, P: PublicId>(
+        observation: &Observation<T, P>,
+    ) -> Option<Self> {
+        match *observation {
+            Observation::OpaquePayload(ref payload) => {
+                // Tag the hash so it can't collide with the hash of any other observation.
+                let hash = payload.canonical_hash()?;
+                Some(ObservationHash(Hash::from(
+                    serialise(&("OpaquePayload", hash)).as_slice(),
+                )))
+            }
+            Observation::Batch(ref payloads) => {
+                let hashes: Vec<_> = payloads
+                    .iter()
+                    .map(T::canonical_hash)
+                    .collect::<Option<_>>()?;
+                Some(ObservationHash(Hash::from(
+                    serialise(&("Batch", hashes)).as_slice(),
+                )))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a, T: NetworkEvent, P: PublicId> From<&'a Observation<T, P>> for ObservationHash {
     fn from(observation: &'a Observation<T, P>) -> Self {
-        ObservationHash(Hash::from(serialise(observation).as_slice()))
+        Self::canonical(observation)
+            .unwrap_or_else(|| ObservationHash(Hash::from(serialise(observation).as_slice())))
     }
 }
 
@@ -297,8 +456,24 @@ impl Debug for ObservationHash {
     }
 }
 
+/// Opaque identifier of an observation. It is derived from the same hash `Parsec` uses internally
+/// to key observations, so two observations have the same ID exactly when `Parsec` treats them as
+/// the same observation (see `NetworkEvent::canonical_hash`).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub struct ObservationId(ObservationHash);
+
+impl ObservationId {
+    pub(crate) fn new(hash: ObservationHash) -> Self {
+        ObservationId(hash)
+    }
+
+    pub(crate) fn hash(&self) -> &ObservationHash {
+        &self.0
+    }
+}
+
 // Container for observation with its metadata.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct ObservationInfo<T: NetworkEvent, P: PublicId> {
     pub(crate) observation: Observation<T, P>,
     pub(crate) consensused: bool,
@@ -332,7 +507,9 @@ impl ObservationKey {
     pub fn new(hash: ObservationHash, creator: PeerIndex, consensus_mode: ConsensusMode) -> Self {
         match consensus_mode {
             ConsensusMode::Single => ObservationKey::Single(hash, creator),
-            ConsensusMode::Supermajority => ObservationKey::Supermajority(hash),
+            ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => {
+                ObservationKey::Supermajority(hash)
+            }
         }
     }
 
@@ -352,6 +529,8 @@ impl ObservationKey {
         }
     }
 
+    // Note: keys of observations in `Threshold` mode are indistinguishable from the ones in
+    // `Supermajority` mode, so this returns `Supermajority` for them.
     pub fn consensus_mode(&self) -> ConsensusMode {
         match *self {
             ObservationKey::Single(..) => ConsensusMode::Single,
@@ -383,24 +562,69 @@ impl ObservationKey {
 }
 
 /// Number of votes necessary to reach consensus on an `OpaquePayload`.
+///
+/// This only affects when an opaque payload becomes a candidate for consensus. The meta-voting
+/// itself (observers, strongly-seeing, meta-vote decisions) always requires a supermajority, as
+/// that is what the safety of the algorithm relies on. Likewise, internal observations such as
+/// `Add`, `Remove` or `Accusation` always require a supermajority, regardless of the mode.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ConsensusMode {
     /// One vote is enough.
     Single,
     /// Supermajority (more than 2/3) is required.
     Supermajority,
+    /// More than `numerator / denominator` of the voters are required. `Supermajority` is
+    /// equivalent to `Threshold { numerator: 2, denominator: 3 }`.
+    ///
+    /// The fraction must be at least 0 and less than 1, i.e. `numerator < denominator`. Like their
+    /// other preconditions, the `Parsec` constructors panic in debug builds if it isn't, and only
+    /// log an error in release builds.
+    Threshold {
+        /// Numerator of the fraction of voters that must be exceeded.
+        numerator: usize,
+        /// Denominator of the fraction of voters that must be exceeded.
+        denominator: usize,
+    },
 }
 
 impl ConsensusMode {
     pub(crate) fn of<T: NetworkEvent, P: PublicId>(self, observation: &Observation<T, P>) -> Self {
         if observation.is_opaque() {
             self
-        } else if observation.is_dkg_message() {
+        } else if observation.is_dkg_message() || observation.is_no_op() {
             ConsensusMode::Single
         } else {
             ConsensusMode::Supermajority
         }
     }
+
+    // Returns whether this mode describes a threshold we can work with.
+    pub(crate) fn is_valid(self) -> bool {
+        match self {
+            ConsensusMode::Single | ConsensusMode::Supermajority => true,
+            ConsensusMode::Threshold {
+                numerator,
+                denominator,
+            } => numerator < denominator,
+        }
+    }
+
+    // Returns whether `did_vote` out of `can_vote` voters is enough for a payload to be considered
+    // for consensus under this mode.
+    pub(crate) fn check(self, did_vote: usize, can_vote: usize) -> bool {
+        match self {
+            ConsensusMode::Single => did_vote > 0,
+            ConsensusMode::Supermajority => ConsensusMode::Threshold {
+                numerator: 2,
+                denominator: 3,
+            }
+            .check(did_vote, can_vote),
+            ConsensusMode::Threshold {
+                numerator,
+                denominator,
+            } => denominator.saturating_mul(did_vote) > numerator.saturating_mul(can_vote),
+        }
+    }
 }
 
 /// Returns whether `small` is more than two thirds of `large`.
@@ -465,4 +689,158 @@ mod tests {
         let serialised = serialise(&before);
         let _: Malice<Transaction, PeerId> = unwrap!(deserialise(&serialised));
     }
+
+    #[test]
+    fn canonical_hash_identifies_opaque_payloads() {
+        // Name identified case-insensitively.
+        #[derive(Clone, Serialize, Deserialize, Debug)]
+        struct Name(String);
+
+        impl PartialEq for Name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for Name {}
+
+        impl PartialOrd for Name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.to_lowercase().cmp(&other.0.to_lowercase())
+            }
+        }
+
+        impl NetworkEvent for Name {
+            fn canonical_hash(&self) -> Option<Hash> {
+                Some(Hash::from(self.0.to_lowercase().as_bytes()))
+            }
+        }
+
+        let hash = |name: &str| {
+            ObservationHash::from(&Observation::OpaquePayload::<_, PeerId>(Name(name.into())))
+        };
+        assert_eq!(hash("Alice"), hash("ALICE"));
+        assert_ne!(hash("Alice"), hash("Bob"));
+
+        // Payloads without canonical hash are still identified by their serialised form.
+        let transaction_hash = |value: &str| {
+            ObservationHash::from(&Observation::OpaquePayload::<_, PeerId>(Transaction::new(
+                value,
+            )))
+        };
+        assert_ne!(transaction_hash("Alice"), transaction_hash("ALICE"));
+    }
+
+    #[test]
+    fn batch_is_single_ordered_observation() {
+        let one = Transaction::new("one");
+        let two = Transaction::new("two");
+
+        let batch = Observation::<_, PeerId>::Batch(vec![one.clone(), two.clone()]);
+        assert!(batch.is_opaque());
+        assert_eq!(batch.opaque_payloads(), &[one.clone(), two.clone()][..]);
+        assert_eq!(ConsensusMode::Single.of(&batch), ConsensusMode::Single);
+
+        let serialised = serialise(&batch);
+        let deserialised: Observation<Transaction, PeerId> = unwrap!(deserialise(&serialised));
+        assert_eq!(deserialised, batch);
+
+        let reversed = Observation::<_, PeerId>::Batch(vec![two, one.clone()]);
+        assert_ne!(
+            ObservationHash::from(&batch),
+            ObservationHash::from(&reversed)
+        );
+        assert_ne!(
+            ObservationHash::from(&batch),
+            ObservationHash::from(&Observation::OpaquePayload(one))
+        );
+    }
+
+    #[test]
+    fn observation_id_matches_internal_hash() {
+        let one = Observation::<_, PeerId>::OpaquePayload(Transaction::new("one"));
+        let two = Observation::<_, PeerId>::OpaquePayload(Transaction::new("two"));
+
+        let id = ObservationId::new(ObservationHash::from(&one));
+        assert_eq!(id.0, ObservationHash::from(&one));
+        assert_eq!(id, ObservationId::new(ObservationHash::from(&one.clone())));
+        assert_ne!(id, ObservationId::new(ObservationHash::from(&two)));
+    }
+
+    #[test]
+    fn consensus_mode_check() {
+        let majority = ConsensusMode::Threshold {
+            numerator: 1,
+            denominator: 2,
+        };
+        assert!(!majority.check(2, 4));
+        assert!(majority.check(3, 4));
+
+        for can_vote in 1..20 {
+            for did_vote in 0..=can_vote {
+                assert_eq!(
+                    ConsensusMode::Supermajority.check(did_vote, can_vote),
+                    is_more_than_two_thirds(did_vote, can_vote)
+                );
+            }
+        }
+
+        assert!(ConsensusMode::Single.check(1, 10));
+        assert!(!ConsensusMode::Single.check(0, 10));
+    }
+
+    #[test]
+    fn consensus_mode_only_applies_to_opaque_payloads() {
+        let peer_id = PeerId::new("Alice");
+        let internal_observations = vec![
+            Observation::<Transaction, PeerId>::Add {
+                peer_id: peer_id.clone(),
+                related_info: vec![],
+            },
+            Observation::Remove {
+                peer_id: peer_id.clone(),
+                related_info: vec![],
+            },
+            Observation::Accusation {
+                offender: peer_id,
+                malice: Malice::Unprovable(UnprovableMalice::Unspecified),
+            },
+        ];
+        let opaque = Observation::OpaquePayload(Transaction::new("one"));
+
+        let modes = vec![
+            ConsensusMode::Single,
+            ConsensusMode::Supermajority,
+            ConsensusMode::Threshold {
+                numerator: 1,
+                denominator: 2,
+            },
+        ];
+        for mode in modes {
+            for observation in &internal_observations {
+                assert_eq!(mode.of(observation), ConsensusMode::Supermajority);
+            }
+            assert_eq!(mode.of(&opaque), mode);
+        }
+    }
+
+    #[test]
+    fn no_op_is_consensused_per_peer_without_a_block() {
+        let no_op = Observation::<Transaction, PeerId>::NoOp(0);
+        assert!(no_op.is_internal());
+        assert_eq!(
+            ConsensusMode::Supermajority.of(&no_op),
+            ConsensusMode::Single
+        );
+        assert_ne!(
+            ObservationHash::from(&no_op),
+            ObservationHash::from(&Observation::<Transaction, PeerId>::NoOp(1))
+        );
+    }
 }
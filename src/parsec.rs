@@ -8,52 +8,106 @@
 
 #[cfg(all(test, feature = "mock"))]
 use crate::dev_utils::ParsedContents;
-#[cfg(all(test, feature = "malice-detection", feature = "mock"))]
-use crate::gossip::EventHash;
 #[cfg(all(test, any(feature = "testing", feature = "mock")))]
 use crate::gossip::GraphSnapshot;
+#[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+use crate::meta_voting::{MetaVoteSnapshot, MetaVoteState};
 #[cfg(feature = "malice-detection")]
-use crate::observation::Malice;
+use crate::observation::{Malice, UnprovableMalice};
 use crate::{
     block::{Block, BlockGroup},
     dump_graph,
-    error::{Error, Result},
+    error::{Error, GraphInconsistency, Result},
     gossip::{
-        Event, EventContextRef, EventIndex, Graph, IndexedEventRef, PackedEvent, Request, Response,
+        CausalRelation, Event, EventContextRef, EventHash, EventIndex, FrontierRequest, Graph,
+        IndexedEventRef, PackedEvent, Request, Response, SyncStats, MIN_SUPPORTED_PROTOCOL_VERSION,
+        PROTOCOL_VERSION,
     },
     id::{PublicId, SecretId},
     key_gen::{
         dkg_threshold, message::DkgMessage, parsec_rng::ParsecRng, Ack, AckOutcome, KeyGen, Part,
         PartOutcome,
     },
-    meta_voting::{MetaElection, MetaEvent, MetaEventBuilder, MetaVote, Observer},
+    memory_report::MemoryReport,
+    meta_voting::{
+        ConsensusConfidence, MetaElection, MetaEvent, MetaEventBuilder, MetaEventInfo, MetaVote,
+        Observer,
+    },
     network_event::NetworkEvent,
     observation::{
-        is_more_than_two_thirds, ConsensusMode, Observation, ObservationHash, ObservationKey,
-        ObservationStore,
+        is_more_than_two_thirds, ConsensusMode, MaliceKind, Observation, ObservationHash,
+        ObservationId, ObservationInfo, ObservationKey, ObservationStore,
+    },
+    parsec_helpers::{defer_payloads_over_quota, find_interesting_content_for_event},
+    peer_list::{
+        MembershipChange, Peer, PeerIndex, PeerIndexMap, PeerIndexSet, PeerList, PeerListChange,
+        PeerState, ResponsivenessStats,
     },
-    parsec_helpers::find_interesting_content_for_event,
-    peer_list::{Peer, PeerIndex, PeerIndexMap, PeerIndexSet, PeerList, PeerListChange, PeerState},
 };
 #[cfg(any(feature = "testing", all(test, feature = "mock")))]
 use crate::{
     hash::Hash,
     mock::{PeerId, Transaction},
 };
-use itertools::Itertools;
+use itertools::{Either, Itertools};
+#[cfg(feature = "timing")]
+use std::time::{Duration, Instant};
 #[cfg(any(test, feature = "testing"))]
-use std::ops::{Deref, DerefMut};
 use std::{
+    borrow::{Borrow, BorrowMut},
+    ops::{Deref, DerefMut},
+};
+use std::{
+    cmp,
     collections::{BTreeMap, BTreeSet, VecDeque},
     iter,
     marker::PhantomData,
     mem,
     num::NonZeroUsize,
-    usize,
+    path::PathBuf,
+    result, usize,
 };
 
 pub(crate) type KeyGenId = usize;
 
+// Unless explicitly set via `set_max_incoming_events`, an incoming gossip message may carry at most
+// this many times as many events as our graph currently holds...
+const INCOMING_EVENTS_LIMIT_FACTOR: usize = 10;
+// ...but never fewer than this, so that full-syncs to nodes with small graphs still pass.
+const MIN_INCOMING_EVENTS_LIMIT: usize = 10_000;
+// Unless explicitly set via `set_max_pending_accusations`, we stop handling an incoming gossip
+// message once it triggers more than this many not yet raised accusations.
+#[cfg(feature = "malice-detection")]
+const DEFAULT_MAX_PENDING_ACCUSATIONS: usize = 1_000;
+// Honest peers occasionally resend events we already have from them, so we only accuse a peer of
+// spamming us with stale gossip once this many consecutive messages from it were stale.
+#[cfg(feature = "malice-detection")]
+pub(crate) const STALE_GOSSIP_LIMIT: usize = 3;
+// Unless explicitly set via `set_max_rounds`, we log a diagnostic once any meta-vote needs more
+// than this many rounds. Termination is only guaranteed with probability 1, but this is far beyond
+// anything an honest section should ever reach.
+const DEFAULT_MAX_ROUNDS: usize = 50;
+// Unless explicitly set via `set_max_observation_size`, we don't vote for nor accept observations
+// whose serialised size exceeds this many bytes.
+const DEFAULT_MAX_OBSERVATION_SIZE: usize = 1024 * 1024;
+// Number of most recent block creation times kept for `consensus_rate`.
+#[cfg(feature = "timing")]
+const BLOCK_TIMES_CAPACITY: usize = 1_000;
+// Number of most recent consensus durations kept for `block_consensus_duration`.
+#[cfg(feature = "timing")]
+const CONSENSUS_DURATIONS_CAPACITY: usize = 1_000;
+
+// Like `log_or_panic!`, but only ever logs if `$panic` is false.
+macro_rules! log_or_panic_if {
+    ($panic:expr, $($arg:tt)*) => {
+        if $panic {
+            log_or_panic!($($arg)*);
+        } else {
+            error!($($arg)*);
+        }
+    };
+}
+
 /// The main object which manages creating and receiving gossip about network events from peers, and
 /// which provides a sequence of consensused [Block](struct.Block.html)s by applying the PARSEC
 /// algorithm. A `Block`'s payload, described by the [Observation](enum.Observation.html) type, is
@@ -113,12 +167,74 @@ pub struct Parsec<T: NetworkEvent, S: SecretId> {
     // Accusations to raise at the end of the processing of current gossip message.
     #[cfg(feature = "malice-detection")]
     pending_accusations: Accusations<T, S::PublicId>,
+    // Maximum number of accusations we allow to be pending at a time.
+    #[cfg(feature = "malice-detection")]
+    max_pending_accusations: usize,
+    // True if accusations need to be approved via `approve_accusation` before being raised.
+    #[cfg(feature = "malice-detection")]
+    manual_accusation_approval: bool,
+    // Accusations waiting to be approved or dismissed, see `set_manual_accusation_approval`.
+    #[cfg(feature = "malice-detection")]
+    unapproved_accusations: Vec<(S::PublicId, Malice<T, S::PublicId>)>,
+    // Accusations dismissed via `dismiss_accusation`, so that they aren't held back again if the
+    // same malice is detected again.
+    #[cfg(feature = "malice-detection")]
+    dismissed_accusations: Vec<(S::PublicId, Malice<T, S::PublicId>)>,
+    // Accusations carried by the events in the graph, grouped by the creator of the event and
+    // ordered by the event's topological index.
+    #[cfg(feature = "malice-detection")]
+    accusations_by_creator: AccusationsByCreator<T, S::PublicId>,
     // Events to be inserted into the gossip graph when this node becomes voter.
     pending_events: Vec<PendingEvent<T, S::PublicId>>,
+    // Time it took to reach consensus on each payload, measured from the insertion of the first
+    // vote for it until its block was created.
+    #[cfg(feature = "timing")]
+    consensus_durations: VecDeque<(ObservationKey, Duration)>,
+    // Time at which the start of the active consensus window last moved forward.
+    #[cfg(feature = "timing")]
+    last_consensus_progress: Instant,
+    // Times at which our most recent blocks were created, oldest first.
+    #[cfg(feature = "timing")]
+    block_times: VecDeque<Instant>,
+    // Maximum number of events we accept in a single incoming gossip message. If `None`, the limit
+    // is derived from the current size of our graph.
+    max_incoming_events: Option<usize>,
+    // Peers to which the next gossip request should carry our whole graph, regardless of what we
+    // think they already know.
+    peers_to_resync: PeerIndexSet,
+    // Callback invoked with (peer, old state, new state) when consensus changes a peer's state.
+    on_peer_state_change: Option<Box<dyn FnMut(&S::PublicId, PeerState, PeerState)>>,
+    // Directory into which this instance dumps its graphs. If `None`, the directory shared by all
+    // instances on the current thread is used.
+    dump_dir: Option<PathBuf>,
+    // Whether to panic (in debug builds) on detecting an internal inconsistency, rather than just
+    // logging it.
+    panic_on_inconsistency: bool,
+    // Maximum number of meta-voting rounds before we give up on the current meta-election.
+    max_rounds: usize,
+    // Maximum serialised size of an observation we vote for or accept from other peers.
+    max_observation_size: usize,
+    // Maximum number of opaque payloads proposed by the same peer which an event's interesting
+    // content lists before those of other peers. `None` if not limited.
+    vote_rate_limit: Option<usize>,
+    // Blocks with a lower index are created but not returned by `poll`.
+    min_block_index: u64,
+    // Maximum number of blocks waiting to be polled before we stop handling gossip.
+    max_consensused_backlog: Option<usize>,
+    // Logical timestamps supplied via `vote_for_at`, keyed by the hash of the event carrying the
+    // vote. They are never used by the algorithm itself.
+    #[cfg(any(test, feature = "testing"))]
+    logical_times: BTreeMap<EventHash, u64>,
+    // True if we never create gossip events of our own, see `observer_only`.
+    observer_only: bool,
     // True to disable processing consensus on this instance to speed up processing for irrelevant
     // parsec instances.
     #[cfg(any(test, feature = "testing"))]
     ignore_process_events: bool,
+    // Test-only override of the coin tosses, called with the round and the peer whose meta-vote is
+    // being computed. If it returns `None`, the coin is tossed as usual.
+    #[cfg(any(test, feature = "testing"))]
+    coin_oracle: Option<Box<dyn Fn(usize, &S::PublicId) -> Option<bool>>>,
     // Provided RNG: Needs to be cryptographically secure RNG as it is used for DKG key generation.
     secure_rng: ParsecRng,
 }
@@ -148,13 +264,15 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         let mut peer_list = PeerList::new(our_id);
         let genesis_indices: PeerIndexSet = genesis_group
             .iter()
-            .map(|peer_id| {
+            .filter_map(|peer_id| {
                 if peer_id == peer_list.our_pub_id() {
                     let peer_index = PeerIndex::OUR;
-                    peer_list.change_peer_state(peer_index, PeerState::active());
-                    peer_index
+                    let _ = peer_list.change_peer_state(peer_index, PeerState::active());
+                    Some(peer_index)
                 } else {
-                    peer_list.add_peer(peer_id.clone(), PeerState::active())
+                    peer_list
+                        .add_peer(peer_id.clone(), PeerState::active())
+                        .ok()
                 }
             })
             .collect();
@@ -189,7 +307,9 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     /// * `genesis_group` is the set of public IDs of the peers that were present at the section
     /// startup.
     /// * `section` is the set of public IDs of the peers that constitute the section at the time
-    /// of joining. They are the peers this `Parsec` instance will accept gossip from.
+    /// of joining, or of just some of them (e.g. the contacts we know about). They are the peers
+    /// this `Parsec` instance will initially accept gossip from. The remaining members are learnt
+    /// from the gossip as we reach consensus on adding them.
     /// * `consensus_mode` determines how many votes are needed for an observation to become a
     /// candidate for consensus. For more details, see [ConsensusMode](enum.ConsensusMode.html)
     /// * `secure_rng` cryptographically secure RNG to use for DKG key generation.
@@ -199,6 +319,33 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         section: &BTreeSet<S::PublicId>,
         consensus_mode: ConsensusMode,
         secure_rng: Box<dyn rand::Rng>,
+    ) -> Self {
+        Self::from_existing_with_state(
+            our_id,
+            genesis_group,
+            section,
+            PeerState::RECV,
+            consensus_mode,
+            secure_rng,
+        )
+    }
+
+    /// Like `from_existing`, but starts us in `our_initial_state` instead of `PeerState::RECV`,
+    /// e.g. to let a joiner which was vetted out-of-band create gossip right away, before the
+    /// section reaches consensus on adding it. `our_initial_state` must include `PeerState::RECV`.
+    ///
+    /// If `our_initial_state` includes `PeerState::SEND`, our initial event is added to the graph
+    /// immediately, as creating gossip requires it, and the genesis group and section members are
+    /// assumed to accept gossip from us already, so they are added with `PeerState::RECV` too.
+    /// They must then have us in their peer list with `PeerState::SEND`, otherwise they reject our
+    /// events. Unless the whole section uses such a handshake, `from_existing` is the safe choice.
+    pub fn from_existing_with_state(
+        our_id: S,
+        genesis_group: &BTreeSet<S::PublicId>,
+        section: &BTreeSet<S::PublicId>,
+        our_initial_state: PeerState,
+        consensus_mode: ConsensusMode,
+        secure_rng: Box<dyn rand::Rng>,
     ) -> Self {
         if genesis_group.is_empty() {
             log_or_panic!("Genesis group can't be empty");
@@ -216,15 +363,33 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             log_or_panic!("Section can't already contain us");
         }
 
+        if !our_initial_state.can_recv() {
+            log_or_panic!("Our initial state must include RECV");
+        }
+
         let mut peer_list = PeerList::new(our_id);
 
         // Add ourselves
-        peer_list.change_peer_state(PeerIndex::OUR, PeerState::RECV);
+        let _ = peer_list.change_peer_state(PeerIndex::OUR, our_initial_state);
+
+        // If we can send gossip already, the section already accepts it from us.
+        let recv_state = if our_initial_state.can_send() {
+            PeerState::RECV
+        } else {
+            PeerState::inactive()
+        };
 
         // Add the genesis group.
         let genesis_indices: PeerIndexSet = genesis_group
             .iter()
-            .map(|peer_id| peer_list.add_peer(peer_id.clone(), PeerState::VOTE | PeerState::SEND))
+            .filter_map(|peer_id| {
+                peer_list
+                    .add_peer(
+                        peer_id.clone(),
+                        PeerState::VOTE | PeerState::SEND | recv_state,
+                    )
+                    .ok()
+            })
             .collect();
 
         // Add the current section members.
@@ -232,10 +397,163 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             if peer_list.contains(peer_id) {
                 continue;
             }
-            let _ = peer_list.add_peer(peer_id.clone(), PeerState::SEND);
+            let _ = peer_list.add_peer(peer_id.clone(), PeerState::SEND | recv_state);
+        }
+
+        let mut parsec = Self::empty(peer_list, genesis_indices, consensus_mode, secure_rng);
+        if our_initial_state.can_send() {
+            parsec.add_initial_event();
+        }
+
+        parsec
+    }
+
+    /// Creates a new `Parsec` for an observer-only node, e.g. an auditor or archival node which
+    /// follows the consensus of an existing section without taking part in it. The arguments are
+    /// the same as for `from_existing`.
+    ///
+    /// An observer never creates gossip events: `vote_for` fails with `Error::ObserverMode`, and
+    /// `handle_request` and `handle_response` add the received events to the graph and compute
+    /// consensus on them, but create neither sync events nor accusations. `poll` yields the
+    /// consensused blocks as usual. As the observer can't send gossip, the section members must
+    /// send it to us, and they should never vote to add us.
+    pub fn observer_only(
+        our_id: S,
+        genesis_group: &BTreeSet<S::PublicId>,
+        section: &BTreeSet<S::PublicId>,
+        consensus_mode: ConsensusMode,
+        secure_rng: Box<dyn rand::Rng>,
+    ) -> Self {
+        let mut parsec =
+            Self::from_existing(our_id, genesis_group, section, consensus_mode, secure_rng);
+        parsec.observer_only = true;
+        parsec
+    }
+
+    /// Creates a new `Parsec` from an externally built peer list, with an empty gossip graph.
+    ///
+    /// * `our_id` is the value that will identify the owning peer in the network. It must be the
+    /// one `peer_list` was created with.
+    /// * `peer_list` is the list of the peers in the section and their states. The voters in it
+    /// are the voters of the first meta-election. If we are one of them, our initial event is
+    /// added to the graph.
+    /// * `consensus_mode` determines how many votes are needed for an observation to become a
+    /// candidate for consensus. For more details, see [ConsensusMode](enum.ConsensusMode.html)
+    /// * `secure_rng` cryptographically secure RNG to use for DKG key generation.
+    ///
+    /// Returns `Error::UnknownPeer` if `peer_list` doesn't contain us, i.e. it belongs to a
+    /// different peer or we are `inactive` in it, or `Error::MissingVoters` if it has no voters.
+    pub fn from_peer_list(
+        our_id: S,
+        peer_list: PeerList<S>,
+        consensus_mode: ConsensusMode,
+        secure_rng: Box<dyn rand::Rng>,
+    ) -> Result<Self> {
+        if peer_list.our_pub_id() != our_id.public_id()
+            || peer_list.our_state() == PeerState::inactive()
+        {
+            return Err(Error::UnknownPeer);
+        }
+
+        let voters: PeerIndexSet = peer_list
+            .voters()
+            .map(|(peer_index, _)| peer_index)
+            .collect();
+        if voters.is_empty() {
+            return Err(Error::MissingVoters);
+        }
+
+        let mut parsec = Self::empty(peer_list, voters, consensus_mode, secure_rng);
+        if parsec.peer_list.our_state().can_vote() {
+            parsec.add_initial_event();
+        }
+
+        Ok(parsec)
+    }
+
+    /// Creates a copy of this `Parsec` which uses `our_id` instead of our secret ID. This allows
+    /// feeding divergent gossip to each copy, e.g. for "what-if" analysis or fuzzing.
+    ///
+    /// * `our_id` must have the same public ID as the original owning peer.
+    /// * `secure_rng` cryptographically secure RNG to use for DKG key generation.
+    ///
+    /// The gossip graph, the observations, the meta-election and the peer list are copied. Any
+    /// in-progress distributed key generation, including the DKG messages still waiting to be
+    /// raised, and the `set_on_peer_state_change` callback are not.
+    ///
+    /// Note that the events the copy creates are signed with `our_id`, so unless it holds the
+    /// original peer's secret key, the copy can't create events other peers accept as the original
+    /// peer's.
+    ///
+    /// Returns `Error::UnknownPeer` if the public ID of `our_id` isn't ours.
+    pub fn clone_with_id(&self, our_id: S, secure_rng: Box<dyn rand::Rng>) -> Result<Self> {
+        if our_id.public_id() != self.our_pub_id() {
+            debug!(
+                "{:?} can't be cloned with a different public ID {:?}",
+                self.our_pub_id(),
+                our_id.public_id()
+            );
+            return Err(Error::UnknownPeer);
         }
 
-        Self::empty(peer_list, genesis_indices, consensus_mode, secure_rng)
+        Ok(Self {
+            peer_list: self.peer_list.clone_with_id(our_id),
+            key_gen: BTreeMap::new(),
+            key_gen_next_id: self.key_gen_next_id,
+            graph: self.graph.clone(),
+            observations: self.observations.clone(),
+            consensused_blocks: self.consensused_blocks.clone(),
+            meta_election: self.meta_election.clone(),
+            consensus_mode: self.consensus_mode,
+            pending_dkg_msgs: vec![],
+            #[cfg(feature = "malice-detection")]
+            pending_accusations: self.pending_accusations.clone(),
+            #[cfg(feature = "malice-detection")]
+            max_pending_accusations: self.max_pending_accusations,
+            #[cfg(feature = "malice-detection")]
+            manual_accusation_approval: self.manual_accusation_approval,
+            #[cfg(feature = "malice-detection")]
+            unapproved_accusations: self.unapproved_accusations.clone(),
+            #[cfg(feature = "malice-detection")]
+            dismissed_accusations: self.dismissed_accusations.clone(),
+            #[cfg(feature = "malice-detection")]
+            accusations_by_creator: self.accusations_by_creator.clone(),
+            pending_events: self
+                .pending_events
+                .iter()
+                .filter(|event| match event {
+                    PendingEvent::DkgMessage { .. } => false,
+                    _ => true,
+                })
+                .cloned()
+                .collect(),
+            max_incoming_events: self.max_incoming_events,
+            peers_to_resync: self.peers_to_resync.clone(),
+            on_peer_state_change: None,
+            dump_dir: self.dump_dir.clone(),
+            panic_on_inconsistency: self.panic_on_inconsistency,
+            max_rounds: self.max_rounds,
+            max_observation_size: self.max_observation_size,
+            vote_rate_limit: self.vote_rate_limit,
+            min_block_index: self.min_block_index,
+            max_consensused_backlog: self.max_consensused_backlog,
+            #[cfg(any(test, feature = "testing"))]
+            logical_times: self.logical_times.clone(),
+            observer_only: self.observer_only,
+            #[cfg(feature = "timing")]
+            consensus_durations: self.consensus_durations.clone(),
+            #[cfg(feature = "timing")]
+            last_consensus_progress: self.last_consensus_progress,
+            #[cfg(feature = "timing")]
+            block_times: self.block_times.clone(),
+
+            #[cfg(any(test, feature = "testing"))]
+            ignore_process_events: self.ignore_process_events,
+            #[cfg(any(test, feature = "testing"))]
+            coin_oracle: None,
+
+            secure_rng: ParsecRng::new(secure_rng),
+        })
     }
 
     // Construct empty `Parsec` with no peers (except us) and no gossip events.
@@ -245,6 +563,10 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         consensus_mode: ConsensusMode,
         secure_rng: Box<dyn rand::Rng>,
     ) -> Self {
+        if !consensus_mode.is_valid() {
+            log_or_panic!("Invalid consensus mode {:?}", consensus_mode);
+        }
+
         dump_graph::init();
 
         Self {
@@ -259,10 +581,41 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             pending_dkg_msgs: vec![],
             #[cfg(feature = "malice-detection")]
             pending_accusations: vec![],
+            #[cfg(feature = "malice-detection")]
+            max_pending_accusations: DEFAULT_MAX_PENDING_ACCUSATIONS,
+            #[cfg(feature = "malice-detection")]
+            manual_accusation_approval: false,
+            #[cfg(feature = "malice-detection")]
+            unapproved_accusations: vec![],
+            #[cfg(feature = "malice-detection")]
+            dismissed_accusations: vec![],
+            #[cfg(feature = "malice-detection")]
+            accusations_by_creator: BTreeMap::new(),
             pending_events: vec![],
+            max_incoming_events: None,
+            peers_to_resync: PeerIndexSet::default(),
+            on_peer_state_change: None,
+            dump_dir: None,
+            panic_on_inconsistency: true,
+            max_rounds: DEFAULT_MAX_ROUNDS,
+            max_observation_size: DEFAULT_MAX_OBSERVATION_SIZE,
+            vote_rate_limit: None,
+            min_block_index: 0,
+            max_consensused_backlog: None,
+            #[cfg(any(test, feature = "testing"))]
+            logical_times: BTreeMap::new(),
+            observer_only: false,
+            #[cfg(feature = "timing")]
+            consensus_durations: VecDeque::new(),
+            #[cfg(feature = "timing")]
+            last_consensus_progress: Instant::now(),
+            #[cfg(feature = "timing")]
+            block_times: VecDeque::new(),
 
             #[cfg(any(test, feature = "testing"))]
             ignore_process_events: false,
+            #[cfg(any(test, feature = "testing"))]
+            coin_oracle: None,
 
             secure_rng: ParsecRng::new(secure_rng),
         }
@@ -273,18 +626,38 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         self.peer_list.our_pub_id()
     }
 
+    /// Returns the consensus mode this instance was constructed with. It can't be changed
+    /// afterwards, and only applies to opaque payloads: `Add`, `Remove`, `Accusation` and the other
+    /// internal observations need a supermajority regardless (see `ConsensusMode`).
+    pub fn consensus_mode(&self) -> ConsensusMode {
+        self.consensus_mode
+    }
+
     /// Inserts the owning peer's vote for `observation` into the gossip graph. The subsequent
     /// gossip messages will spread the vote to other peers, eventually making it a candidate for
     /// the next consensused block.
     ///
     /// Returns an error if the owning peer is not a full member of the section yet, if it has
-    /// already voted for this `observation`, or if adding a gossip event containing the vote to
-    /// the gossip graph failed.
+    /// already voted for this `observation`, if the `observation` carries a payload which fails
+    /// `NetworkEvent::validate`, or if adding a gossip event containing the vote to the gossip
+    /// graph failed. Always returns `Error::ObserverMode` on an observer-only node.
     pub fn vote_for(&mut self, observation: Observation<T, S::PublicId>) -> Result<()> {
         debug!("{:?} voting for {:?}", self.our_pub_id(), observation);
 
+        if self.observer_only {
+            return Err(Error::ObserverMode);
+        }
+
         self.confirm_self_state(PeerState::VOTE)?;
 
+        if !observation.opaque_payloads().iter().all(T::validate) {
+            return Err(Error::InvalidObservation);
+        }
+
+        if observation.serialised_size() > self.max_observation_size {
+            return Err(Error::ObservationTooLarge);
+        }
+
         if self.have_voted_for(&observation) {
             return Err(Error::DuplicateVote);
         }
@@ -298,6 +671,42 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Ok(())
     }
 
+    /// Votes for the removal of the owning peer from the section, i.e. for
+    /// `Observation::Remove { peer_id: our_id, .. }`. Returns an error in the same cases as
+    /// `vote_for`.
+    ///
+    /// The recommended shutdown sequence for a peer leaving the section is:
+    ///
+    /// 1. Call `vote_to_leave`.
+    /// 2. Keep gossiping and calling `poll` as usual. Our removal is consensused once `poll`
+    ///    returns the block for our `Remove` observation, at which point `was_removed` returns
+    ///    `true` too. Keep polling until `None`, as the blocks consensused before ours must still
+    ///    be handled.
+    /// 3. Stop initiating gossip. From now on `create_gossip` and `handle_response` return
+    ///    `Error::InvalidSelfState`.
+    /// 4. Keep handling requests for a grace period (e.g. a few gossip rounds) instead of
+    ///    disconnecting immediately. The other peers may not have reached consensus on our removal
+    ///    yet, and until they do, they may still send us gossip. `handle_request` no longer
+    ///    processes the events of such requests, but its `Response` still carries the events the
+    ///    requester may lack, from which it can learn of our removal.
+    pub fn vote_to_leave(&mut self) -> Result<()> {
+        let observation = Observation::Remove {
+            peer_id: self.our_pub_id().clone(),
+            related_info: vec![],
+        };
+        self.vote_for(observation)
+    }
+
+    /// Returns whether we have reached consensus on removing the owning peer from the section. See
+    /// `vote_to_leave` for how to shut down gracefully once this returns `true`.
+    ///
+    /// Note this can become `true` before `poll` returns the block with our removal.
+    pub fn was_removed(&self) -> bool {
+        self.peer_list
+            .get(PeerIndex::OUR)
+            .map_or(false, |peer| peer.removal_event().is_some())
+    }
+
     /// Returns an iterator with the IDs of peers who the owning peer can send gossip messages to.
     /// Calling `create_gossip` with a peer ID returned by this method is guaranteed to succeed
     /// (assuming no section mutation happened in between).
@@ -307,12 +716,56 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .map(|(_, peer)| peer.id())
     }
 
+    /// Returns the IDs of the gossip recipients which we think are missing some of our events, i.e.
+    /// those for which `create_gossip` would be worth the round trip.
+    ///
+    /// A recipient is considered up to date if the latest event of theirs we know of already has
+    /// our latest event as an ancestor.
+    pub fn peers_needing_gossip(&self) -> Vec<&S::PublicId> {
+        let our_last_index_by_creator = if let Some(event) = self
+            .peer_list
+            .last_event(PeerIndex::OUR)
+            .and_then(|event_index| self.graph.get(event_index))
+        {
+            event.index_by_creator()
+        } else {
+            return vec![];
+        };
+
+        self.peer_list
+            .gossip_recipients()
+            .filter(|(peer_index, _)| {
+                self.peer_list
+                    .last_event(*peer_index)
+                    .and_then(|event_index| self.graph.get(event_index))
+                    .and_then(|event| event.last_ancestor_by(PeerIndex::OUR))
+                    .map(|last_ancestor| last_ancestor < our_last_index_by_creator)
+                    .unwrap_or(true)
+            })
+            .map(|(_, peer)| peer.id())
+            .collect()
+    }
+
     /// Creates a new message to be gossiped to a peer, containing all gossip events this peer
     /// thinks that peer needs.  If the given peer is not an active node, an error is returned.
     ///
     /// * `peer_id`: the intended recipient of the gossip message
     /// * returns a `Request` to be sent to the intended recipient
     pub fn create_gossip(&mut self, peer_id: &S::PublicId) -> Result<Request<T, S::PublicId>> {
+        let packed_events = self
+            .create_gossip_iter(peer_id)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Request::new(packed_events))
+    }
+
+    /// Like `create_gossip`, but instead of a `Request` returns an iterator over the gossip events
+    /// the request would carry. The events are packed lazily, one at a time, so the caller can
+    /// serialise and stream them without first holding all of them in memory, which matters when
+    /// the recipient doesn't know any of our events yet and so needs our whole graph.
+    pub fn create_gossip_iter<'a>(
+        &'a mut self,
+        peer_id: &S::PublicId,
+    ) -> Result<impl Iterator<Item = Result<PackedEvent<T, S::PublicId>>> + 'a> {
         let peer_index = self.get_peer_index(peer_id)?;
         self.confirm_allowed_to_gossip_to(peer_index)?;
 
@@ -322,45 +775,158 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             peer_id
         );
 
-        let self_parent = self.peer_list.last_event(PeerIndex::OUR).ok_or_else(|| {
-            log_or_panic!("{:?} missing our own last event hash.", self.our_pub_id());
-            Error::Logic
-        })?;
-        let sync_event = Event::new_from_requesting(self_parent, peer_id, self.event_context())?;
-        let _ = self.add_event(sync_event)?;
+        self.create_requesting_event(peer_id)?;
 
-        let events = if self.peer_list.last_event(peer_index).is_some() {
-            self.events_to_gossip_to_peer(peer_index)?
+        let resync = self.peers_to_resync.remove(peer_index);
+        let this: &'a Self = self;
+        let events = if !resync && this.peer_list.last_event(peer_index).is_some() {
+            Either::Left(this.events_to_gossip_to_peer(peer_index)?)
         } else {
-            self.graph.iter().map(|e| e.inner()).collect()
+            Either::Right(this.graph.iter().map(|e| e.inner()))
         };
-        self.pack_events(events).map(Request::new)
+        let event_context = this.event_context();
+        Ok(events.map(move |event| event.pack(event_context)))
+    }
+
+    /// Creates a message declaring which gossip events we already hold, to be sent to a peer
+    /// instead of waiting for a `Request` carrying everything that peer thinks we need. The peer
+    /// answers it via `handle_frontier_request` with a `Request` carrying only the events we lack,
+    /// which we then handle as usual. This saves bandwidth when we are nearly in sync with the
+    /// peer.
+    pub fn create_frontier_request(&self) -> FrontierRequest<S::PublicId> {
+        let frontier = self
+            .peer_list
+            .all_ids()
+            .filter_map(|(peer_index, peer_id)| {
+                self.peer_list
+                    .last_event(peer_index)
+                    .and_then(|event_index| self.graph.get(event_index))
+                    .map(|event| (peer_id.clone(), event.index_by_creator()))
+            })
+            .collect();
+        FrontierRequest::new(frontier)
+    }
+
+    /// Handles a `FrontierRequest` received from the `src` peer. Like `create_gossip`, this
+    /// creates a new gossip event and returns a `Request` to be sent to `src`, but the request
+    /// only carries the events `src` declared it doesn't hold, rather than those we think it
+    /// needs. Returns an error in the same cases as `create_gossip`.
+    ///
+    /// The declaration is taken at face value: a peer understating what it holds merely receives
+    /// more events than it needs, and one overstating it receives fewer and may then fail to
+    /// handle the request, so lying only hurts the liar. For the same reason, an honest peer which
+    /// holds only one side of a fork may also fail to handle the request, in which case it should
+    /// fall back to the regular `create_gossip` exchange.
+    pub fn handle_frontier_request(
+        &mut self,
+        src: &S::PublicId,
+        frontier_request: FrontierRequest<S::PublicId>,
+    ) -> Result<Request<T, S::PublicId>> {
+        debug!(
+            "{:?} received frontier request from {:?}",
+            self.our_pub_id(),
+            src
+        );
+
+        Self::confirm_supported_protocol_version(frontier_request.protocol_version)?;
+        let src_index = self.get_peer_index(src)?;
+        self.confirm_allowed_to_gossip_to(src_index)?;
+        self.create_requesting_event(src)?;
+
+        let frontier = frontier_request.frontier;
+        let events = self.graph.iter().filter(|event| {
+            self.peer_list
+                .get(event.creator())
+                .and_then(|peer| frontier.get(peer.id()))
+                .map_or(true, |index_by_creator| {
+                    event.index_by_creator() > *index_by_creator
+                })
+        });
+        self.pack_events(events.map(|event| event.inner()))
+            .map(Request::new)
+    }
+
+    /// Checks, without modifying any state, whether a `Request` or `Response` from `src` would
+    /// currently be accepted based on our own state and that of `src`. Returns the same error
+    /// `handle_request` or `handle_response` would fail with before even looking at the message's
+    /// events, so callers can skip deserialising gossip which would be rejected anyway.
+    pub fn can_handle_gossip_from(&self, src: &S::PublicId) -> Result<()> {
+        let src_index = self.get_peer_index(src)?;
+        self.confirm_can_handle_gossip_from(src_index)
     }
 
     /// Handles a `Request` the owning peer received from the `src` peer.  Returns a `Response` to
     /// be sent back to `src`, or `Err` if the request was not valid or if `src` has been removed
     /// from the section already.
+    ///
+    /// If the request has been handled already, e.g. because the transport redelivered it, it's
+    /// ignored and the returned `Response` is empty, so there is no need to send it.
+    ///
+    /// Once we've been removed from the section, the request's events are ignored, but the
+    /// `Response` still carries our events (see `vote_to_leave`).
     pub fn handle_request(
         &mut self,
         src: &S::PublicId,
         req: Request<T, S::PublicId>,
     ) -> Result<Response<T, S::PublicId>> {
+        self.handle_request_with_stats(src, req)
+            .map(|(response, _)| response)
+    }
+
+    /// Like `handle_request`, but also returns a `SyncStats` describing what handling the request
+    /// achieved.
+    pub fn handle_request_with_stats(
+        &mut self,
+        src: &S::PublicId,
+        req: Request<T, S::PublicId>,
+    ) -> Result<(Response<T, S::PublicId>, SyncStats)> {
         debug!(
             "{:?} received gossip request from {:?}",
             self.our_pub_id(),
             src
         );
 
+        Self::confirm_supported_protocol_version(req.protocol_version)?;
         let src_index = self.get_peer_index(src)?;
+        if self.was_removed() {
+            // The events of the request aren't handled at all, so there is nothing to report.
+            return self
+                .respond_after_removal(src_index)
+                .map(|response| (response, SyncStats::default()));
+        }
+
+        let num_known_events = self.graph.len();
+        let num_known_blocks = self.num_consensused_blocks();
+        #[cfg(feature = "malice-detection")]
+        let num_known_accusations = self.pending_accusations.len();
         let other_parent = self.unpack_and_add_events(src_index, req.packed_events)?;
+        let mut stats = SyncStats {
+            new_events: self.graph.len() - num_known_events,
+            ..SyncStats::default()
+        };
+        #[cfg(feature = "malice-detection")]
+        {
+            stats.accusations_raised = self.num_accusations_since(num_known_accusations);
+        }
+        if stats.new_events == 0 && self.have_synced_with(other_parent) {
+            debug!(
+                "{:?} received duplicate gossip request from {:?}",
+                self.our_pub_id(),
+                src
+            );
+            return Ok((Response::new(vec![]), stats));
+        }
+
         self.create_dkg_events()?;
         #[cfg(feature = "malice-detection")]
         self.create_accusation_events(other_parent)?;
         self.create_sync_event(true, other_parent)?;
         self.flush_pending_events()?;
+        stats.blocks_consensused = self.num_consensused_blocks() - num_known_blocks;
 
         let events = self.events_to_gossip_to_peer(src_index)?;
-        self.pack_events(events).map(Response::new)
+        self.pack_events(events)
+            .map(|packed_events| (Response::new(packed_events), stats))
     }
 
     /// Handles a `Response` the owning peer received from the `src` peer. Returns `Err` if the
@@ -370,82 +936,1020 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         src: &S::PublicId,
         resp: Response<T, S::PublicId>,
     ) -> Result<()> {
+        self.handle_response_with_stats(src, resp).map(|_| ())
+    }
+
+    /// Like `handle_response`, but returns a `SyncStats` describing what handling the response
+    /// achieved.
+    pub fn handle_response_with_stats(
+        &mut self,
+        src: &S::PublicId,
+        resp: Response<T, S::PublicId>,
+    ) -> Result<SyncStats> {
         debug!(
             "{:?} received gossip response from {:?}",
             self.our_pub_id(),
             src
         );
 
+        Self::confirm_supported_protocol_version(resp.protocol_version)?;
         let src_index = self.get_peer_index(src)?;
+        let num_known_events = self.graph.len();
+        let num_known_blocks = self.num_consensused_blocks();
+        #[cfg(feature = "malice-detection")]
+        let num_known_accusations = self.pending_accusations.len();
         let other_parent = self.unpack_and_add_events(src_index, resp.packed_events)?;
+        let mut stats = SyncStats {
+            new_events: self.graph.len() - num_known_events,
+            ..SyncStats::default()
+        };
+        #[cfg(feature = "malice-detection")]
+        {
+            stats.accusations_raised = self.num_accusations_since(num_known_accusations);
+        }
+
         self.create_dkg_events()?;
         #[cfg(feature = "malice-detection")]
         self.create_accusation_events(other_parent)?;
         self.create_sync_event(false, other_parent)?;
-        self.flush_pending_events()
+        self.flush_pending_events()?;
+        stats.blocks_consensused = self.num_consensused_blocks() - num_known_blocks;
+
+        Ok(stats)
     }
 
-    /// Returns the next stable block, if any. The method might need to be called more than once
-    /// for the caller to get all the blocks that have been consensused. A `None` value means that
-    /// all the blocks consensused so far have already been returned.
+    /// Imports events from a trusted source, e.g. an archive of the gossip graph signed by a quorum
+    /// of the section. Returns the number of events that were newly added to our graph.
     ///
-    /// Once the owning peer has been removed from the section (i.e. a block with payload
-    /// `Observation::Remove(our_id)` has been made stable), then no further blocks will be
-    /// enqueued. So, once `poll()` returns such a block, it will continue to return `None` forever.
-    pub fn poll(&mut self) -> Option<Block<T, S::PublicId>> {
-        let mut block_group = self.batch_poll()?;
-        let block = block_group.pop_front()?;
-        if !block_group.is_empty() {
-            self.consensused_blocks.push_front(block_group);
+    /// The events must be in topological order: each event's parents must either be in our graph
+    /// already or precede it in `events`, otherwise the import stops with an error. Events we
+    /// already have are skipped. The events are processed as usual, but they are _not_ checked
+    /// for malice, so the caller is responsible for making sure the source can be trusted.
+    pub fn import_events(&mut self, events: Vec<PackedEvent<T, S::PublicId>>) -> Result<usize> {
+        debug!("{:?} importing {} events", self.our_pub_id(), events.len());
+
+        let mut num_added = 0;
+        for packed_event in events {
+            if let Some(event) = self.unpack(packed_event)? {
+                let _ = self.add_event_without_malice_detection(event)?;
+                num_added += 1;
+            }
         }
-        Some(block)
+
+        Ok(num_added)
+    }
+
+    /// Discards the meta-events of the current meta-election and recreates them from the gossip
+    /// graph. No new gossip events are created. This is meant to recover from a transient
+    /// inconsistency in the consensus state; on a consistent state, it yields exactly the same
+    /// meta-votes as before.
+    ///
+    /// Blocks are only created for payloads which aren't consensused yet, so this never emits a
+    /// block for a payload already returned by `poll()` or still waiting to be polled. Returns an
+    /// error if we can't receive gossip, i.e. aren't processing events.
+    pub fn recompute_consensus(&mut self) -> Result<()> {
+        self.confirm_self_state(PeerState::RECV)?;
+
+        debug!("{:?} recomputing consensus", self.our_pub_id());
+        let start_index = self.meta_election.reset_meta_events();
+        self.process_events(start_index)
+    }
+
+    /// Gives up on reaching consensus on the opaque `observation`: the events carrying it stop
+    /// counting as votes for it, so it is no longer considered interesting and no longer holds back
+    /// the start of the next meta-election. This also applies to the events carrying it which we
+    /// receive until that meta-election starts, but votes received afterwards count again, so the
+    /// payload can still be consensused if peers vote for it anew. The meta-votes of the current
+    /// meta-election are then recomputed. This is meant as a last resort for a payload which can
+    /// never gather enough votes, e.g. because its voters were removed before it could.
+    ///
+    /// **Warning**: this is a local decision, not an agreed one. Unless every node abandons the
+    /// same observation at the same point of the gossip graph (e.g. in response to a block all of
+    /// them consensused), nodes may disagree about which payloads are interesting and so reach
+    /// different consensus, breaking the safety of the whole network.
+    ///
+    /// Returns `Error::InvalidObservation` if the observation isn't opaque, as `Parsec` relies on
+    /// reaching consensus on its internal observations, `Error::UnknownPayload` if it isn't
+    /// currently waiting for consensus, and an error if we can't receive gossip.
+    pub fn abandon_unconsensused(
+        &mut self,
+        observation: &Observation<T, S::PublicId>,
+    ) -> Result<()> {
+        self.confirm_self_state(PeerState::RECV)?;
+        if !observation.is_opaque() {
+            return Err(Error::InvalidObservation);
+        }
+
+        let hash = ObservationHash::from(observation);
+        if !self.meta_election.abandon_payload(hash, self.graph.len()) {
+            return Err(Error::UnknownPayload);
+        }
+
+        warn!(
+            "{:?} abandoning consensus on {:?}",
+            self.our_pub_id(),
+            observation
+        );
+        let start_index = self.meta_election.reset_meta_events();
+        self.process_events(start_index)
+    }
+
+    /// Sets the maximum number of events a single incoming `Request` or `Response` may carry.
+    /// Messages exceeding the limit are rejected with `Error::GossipTooLarge` before any of their
+    /// events are processed.
+    ///
+    /// By default, the limit is a generous multiple of the current size of our gossip graph, so
+    /// that legitimate full-syncs still pass.
+    pub fn set_max_incoming_events(&mut self, limit: usize) {
+        self.max_incoming_events = Some(limit);
+    }
+
+    /// Sets the maximum number of consensused blocks which may be waiting to be returned by
+    /// `poll()` (unlimited by default). While more are waiting, `handle_request` and
+    /// `handle_response` reject incoming gossip with `Error::ConsensusBacklogFull` before looking
+    /// at its events, leaving our state untouched, and `can_handle_gossip_from` returns the same
+    /// error. The caller should drain the blocks via `poll()` first. As `Error` can't carry a
+    /// `Response`, the sender isn't sent one then, so it will simply gossip with us again later.
+    pub fn set_max_consensused_backlog(&mut self, limit: usize) {
+        self.max_consensused_backlog = Some(limit);
+    }
+
+    /// Returns the number of consensused blocks waiting to be returned by `poll()`.
+    pub fn consensused_backlog(&self) -> usize {
+        self.num_consensused_blocks()
+    }
+
+    /// Forgets what we know about which gossip events the given peer has already seen, so the next
+    /// gossip request we create for it carries our whole graph again. This can be used to recover
+    /// a peer whose view diverged, e.g. because it lost some events. Neither our gossip graph nor
+    /// our consensus state is affected.
+    pub fn forget_peer_gossip_state(&mut self, peer_id: &S::PublicId) -> Result<()> {
+        let peer_index = self.get_peer_index(peer_id)?;
+        let _ = self.peers_to_resync.insert(peer_index);
+        Ok(())
+    }
+
+    /// Sets a callback to be invoked with the peer's ID, its old state and its new state whenever
+    /// the state of a peer changes as a result of consensus on adding or removing it. State
+    /// changes made while handling gossip, such as allowing a peer to receive gossip from us once
+    /// it has sent us some, don't trigger the callback.
+    pub fn set_on_peer_state_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(&S::PublicId, PeerState, PeerState) + 'static,
+    {
+        self.on_peer_state_change = Some(Box::new(callback));
+    }
+
+    /// Sets the directory into which this instance dumps its gossip graphs, both on consensus and
+    /// when it is dropped. This allows keeping the graphs of several instances running in the same
+    /// process apart. No-op if the `dump-graphs` feature is not enabled.
+    pub fn set_dump_dir(&mut self, path: PathBuf) {
+        self.dump_dir = Some(path);
+    }
+
+    /// Sets whether detecting an internal inconsistency panics in debug builds (the default) or
+    /// is only logged as an error, as in release builds. In the latter case, the operation which
+    /// hit the inconsistency fails with `Error::Logic` where it can report an error, and carries
+    /// on as best it can otherwise. Graphs are still dumped when this instance is dropped either
+    /// way, so the state leading to the inconsistency can be inspected.
+    ///
+    /// Inconsistencies detected by the internal data structures, such as the peer list and the
+    /// meta-election, are always only logged and fail the operation with `Error::Logic`.
+    pub fn set_panic_on_inconsistency(&mut self, panic: bool) {
+        self.panic_on_inconsistency = panic;
+    }
+
+    /// Sets the maximum number of meta-voting rounds expected in a meta-election (50 by default).
+    /// When a meta-vote first exceeds this many rounds, the meta-vote state is logged as an error
+    /// to help diagnosing why the election doesn't terminate. Meta-voting itself carries on as
+    /// usual, so this never changes the outcome of consensus.
+    ///
+    /// This is deliberately not a hard bound, and exceeding it is not an error: an election cut
+    /// short at the limit could never be decided, and peers that hit the limit at different events
+    /// would disagree about the meta-votes, so consensus would no longer be deterministic.
+    ///
+    /// All peers of a section should use the same limit, so that they all report the same
+    /// pathological elections.
+    pub fn set_max_rounds(&mut self, max_rounds: usize) {
+        self.max_rounds = max_rounds;
+    }
+
+    /// Sets the maximum serialised size, in bytes, of an observation (1 MiB by default).
+    /// `vote_for` returns `Error::ObservationTooLarge` for larger observations. Events carrying
+    /// them are always rejected with `Error::InvalidEvent` and, with malice detection enabled,
+    /// their creator is also accused of `Malice::Unprovable`, as peers which never received such
+    /// an event can't verify the accusation.
+    ///
+    /// All peers of a section must use the same limit. Otherwise, peers with a lower limit reject
+    /// events the others accept, and accuse their creators of malice the others don't see.
+    pub fn set_max_observation_size(&mut self, max_size: usize) {
+        self.max_observation_size = max_size;
+    }
+
+    /// Limits how many opaque payloads proposed by the same peer may become consensused ahead of
+    /// those proposed by other peers, so that a single peer can't monopolise the blocks. Whenever
+    /// an event finds more than `peer_quota` opaque payloads proposed by the same peer interesting
+    /// at once, the extra ones are ordered after all the others. A payload's proposer is the peer
+    /// which voted for it first, as seen by the event, with ties between concurrent votes broken
+    /// by comparing the peers' public IDs. Unlimited by default.
+    ///
+    /// This only depends on the gossip graph, so it is deterministic, but all peers of a section
+    /// must use the same limit, otherwise they may consensus blocks in different orders.
+    pub fn set_vote_rate_limit(&mut self, peer_quota: usize) {
+        self.vote_rate_limit = Some(peer_quota);
+    }
+
+    /// Stops blocks whose `Block::index` is lower than `up_to` from being returned by `poll`. This
+    /// is meant for a peer joining the section via `from_existing`, which would otherwise see the
+    /// blocks of the whole history it catches up on. Consensus on the suppressed blocks is still
+    /// reached and handled internally as usual, e.g. membership changes still take effect. Only
+    /// blocks consensused after this call are affected, and DKG result blocks are never suppressed.
+    pub fn set_suppress_historical_blocks(&mut self, up_to: u64) {
+        self.min_block_index = up_to;
+    }
+
+    /// Sets the maximum number of accusations which may be waiting to be raised. An incoming
+    /// `Request` or `Response` which pushes the number of pending accusations over this limit is
+    /// rejected with `Error::TooManyAccusations` and its remaining events are not processed. The
+    /// accusations over the limit are dropped, so that further messages can still be handled, and
+    /// the remaining ones are raised on handling the next valid message.
+    #[cfg(feature = "malice-detection")]
+    pub fn set_max_pending_accusations(&mut self, limit: usize) {
+        self.max_pending_accusations = limit;
+    }
+
+    /// Sets whether the accusations we raise need to be approved first, e.g. by a human operator,
+    /// since consensus on an accusation removes the offender from the section. If enabled, the
+    /// malice we detect is held back and listed by `review_pending_accusations` instead, and only
+    /// raised once approved via `approve_accusation`. Unapproved accusations never become events.
+    /// At most `set_max_pending_accusations` of them are held back at a time; further malice is
+    /// only logged until some of them are approved or dismissed.
+    ///
+    /// Note that the other peers may accuse us of being an accomplice if we hold back an
+    /// accusation they know we should have raised. Disabling this doesn't raise the accusations
+    /// already held back; they still need to be approved or dismissed.
+    #[cfg(feature = "malice-detection")]
+    pub fn set_manual_accusation_approval(&mut self, enabled: bool) {
+        self.manual_accusation_approval = enabled;
+    }
+
+    /// Returns the accusations held back for approval, see `set_manual_accusation_approval`. Each
+    /// lists the offender and its malice, in the order the malice was detected.
+    #[cfg(feature = "malice-detection")]
+    pub fn review_pending_accusations(&self) -> &[(S::PublicId, Malice<T, S::PublicId>)] {
+        &self.unapproved_accusations
+    }
+
+    /// Approves the accusation at `index` in `review_pending_accusations`, so it's raised when we
+    /// handle the next gossip message. The indices of the subsequent accusations shift down by one.
+    ///
+    /// Returns `Error::UnknownAccusation` if there's no accusation at `index`, or
+    /// `Error::UnknownPeer` if the offender has been removed from our peer list meanwhile, in
+    /// which case the accusation is discarded.
+    #[cfg(feature = "malice-detection")]
+    pub fn approve_accusation(&mut self, index: usize) -> Result<()> {
+        if index >= self.unapproved_accusations.len() {
+            return Err(Error::UnknownAccusation);
+        }
+
+        let (offender, malice) = self.unapproved_accusations.remove(index);
+        let offender = self.get_peer_index(&offender)?;
+        self.pending_accusations.push((offender, malice));
+        Ok(())
+    }
+
+    /// Discards the accusation at `index` in `review_pending_accusations` without raising it. The
+    /// indices of the subsequent accusations shift down by one. The dismissal is remembered, so
+    /// detecting the same malice again doesn't hold it back for review again. Returns
+    /// `Error::UnknownAccusation` if there's no accusation at `index`.
+    #[cfg(feature = "malice-detection")]
+    pub fn dismiss_accusation(&mut self, index: usize) -> Result<()> {
+        if index >= self.unapproved_accusations.len() {
+            return Err(Error::UnknownAccusation);
+        }
+
+        let (offender, malice) = self.unapproved_accusations.remove(index);
+        info!(
+            "{:?} dismissed accusation of {:?} for {:?}",
+            self.our_pub_id(),
+            offender,
+            malice
+        );
+        self.dismissed_accusations.push((offender, malice));
+        Ok(())
+    }
+
+    /// Returns the next stable block, if any. The method might need to be called more than once
+    /// for the caller to get all the blocks that have been consensused. A `None` value means that
+    /// all the blocks consensused so far have already been returned.
+    ///
+    /// Once the owning peer has been removed from the section (i.e. a block with payload
+    /// `Observation::Remove(our_id)` has been made stable), then no further blocks will be
+    /// enqueued. So, once `poll()` returns such a block, it will continue to return `None` forever.
+    pub fn poll(&mut self) -> Option<Block<T, S::PublicId>> {
+        let mut block_group = self.batch_poll()?;
+        let block = block_group.pop_front()?;
+        if !block_group.is_empty() {
+            self.consensused_blocks.push_front(block_group);
+        }
+        Some(block)
+    }
+
+    /// Returns the next group of stable blocks, if any. The method might need to be called more
+    /// than once for the caller to get all the blocks that have been consensused. A `None` value
+    /// means that all the blocks consensused so far have already been returned.
+    ///
+    /// Once the owning peer has been removed from the section (i.e. a block with payload
+    /// `Observation::Remove(our_id)` has been made stable), then no further blocks will be
+    /// enqueued. So, once `poll()` or `batch_poll()` returns such a block, it will continue to
+    /// return `None` forever.
+    pub(crate) fn batch_poll(&mut self) -> Option<BlockGroup<T, S::PublicId>> {
+        self.consensused_blocks.pop_front()
+    }
+
+    // Number of consensused blocks which haven't been returned by `poll()` yet.
+    fn num_consensused_blocks(&self) -> usize {
+        self.consensused_blocks
+            .iter()
+            .map(|group| group.len())
+            .sum()
+    }
+
+    /// Returns the most recently consensused block which hasn't been returned by `poll()` yet,
+    /// without removing it from the queue.
+    pub fn last_block(&self) -> Option<&Block<T, S::PublicId>> {
+        self.consensused_blocks
+            .back()
+            .and_then(|block_group| block_group.back())
+    }
+
+    /// Returns the block the next call to `poll()` would return, without removing it from the
+    /// queue.
+    pub fn peek_next(&self) -> Option<&Block<T, S::PublicId>> {
+        self.consensused_blocks
+            .front()
+            .and_then(|block_group| block_group.front())
+    }
+
+    /// Check if the owning peer can vote (that is, it has reached a consensus on itself being a
+    /// full member of the section).
+    pub fn can_vote(&self) -> bool {
+        self.peer_list.our_state().can_vote()
+    }
+
+    /// Returns the IDs of the members of the section as last agreed by consensus, i.e. the peers,
+    /// the owning peer included, whose addition we have reached consensus on and whose removal we
+    /// haven't. Peers we only know about, such as those passed as `section` to `from_existing`,
+    /// aren't included until we reach consensus on adding them.
+    ///
+    /// This can differ from the voters of the current meta-election during membership churn: those
+    /// are fixed when the meta-election starts, while this set reflects each membership change as
+    /// soon as it's consensused.
+    pub fn section_members(&self) -> BTreeSet<S::PublicId> {
+        self.peer_list
+            .voters()
+            .map(|(_, peer)| peer.id().clone())
+            .collect()
+    }
+
+    /// Returns the number of peers currently allowed to vote, the owning peer included if it is
+    /// one of them.
+    pub fn active_peer_count(&self) -> usize {
+        self.peer_list.voters().count()
+    }
+
+    /// Returns the minimum number of the current voters which form a supermajority (more than two
+    /// thirds of them).
+    pub fn quorum_size(&self) -> usize {
+        2 * self.active_peer_count() / 3 + 1
+    }
+
+    /// Checks if `count` of the current voters form a supermajority (more than two thirds of them).
+    pub fn has_quorum(&self, count: usize) -> bool {
+        is_more_than_two_thirds(count, self.active_peer_count())
+    }
+
+    /// Checks if the given `observation` has already been voted for by the owning peer.
+    pub fn have_voted_for(&self, observation: &Observation<T, S::PublicId>) -> bool {
+        let hash = ObservationHash::from(observation);
+        let key = ObservationKey::new(hash, PeerIndex::OUR, self.consensus_mode.of(observation));
+        self.observations
+            .get(&key)
+            .map(|info| info.created_by_us)
+            .unwrap_or(false)
+    }
+
+    /// Checks if the given `observation` is interesting to any peer in the current meta-election,
+    /// i.e. from the perspective of one of its events, enough peers have voted for it to make it a
+    /// candidate for the next block. This is a softer signal than consensus, e.g. for showing
+    /// progress. Once the observation is consensused, this returns `false` again.
+    pub fn is_interesting(&self, observation: &Observation<T, S::PublicId>) -> bool {
+        self.meta_election
+            .is_interesting_payload(&ObservationHash::from(observation))
+    }
+
+    /// Check if there are any observations that have been voted for but not yet polled - that is,
+    /// either they haven't been consensused yet or a block containing that observation hasn't yet
+    /// been retrieved by calling `poll`.
+    pub fn has_unpolled_observations(&self) -> bool {
+        self.observations.values().any(|info| !info.consensused)
+            || !self.consensused_blocks.is_empty()
+    }
+
+    /// Returns observations voted for by the owning peer which haven't been returned as a stable
+    /// block by `poll` yet.
+    /// This includes observations that are either not yet consensused or that are already
+    /// consensused, but not yet popped out of the consensus queue.
+    ///
+    /// The observations are sorted first by the consensus order, then by the vote order.
+    pub fn our_unpolled_observations(&self) -> impl Iterator<Item = &Observation<T, S::PublicId>> {
+        self.our_consensused_observations()
+            .chain(self.our_unconsensused_observations())
+    }
+
+    /// Returns the ID under which `Parsec` keys the given observation. This allows external records
+    /// to be keyed the same way as `Parsec`'s own.
+    pub fn observation_id(observation: &Observation<T, S::PublicId>) -> ObservationId {
+        ObservationId::new(ObservationHash::from(observation))
+    }
+
+    /// Returns all the observations voted for by the owning peer, in the order we voted for them,
+    /// i.e. the order of our events carrying the votes. Unlike `our_unpolled_observations`, this
+    /// ignores consensus altogether, so it can be used to deterministically replay our votes. This
+    /// includes our `Genesis` and `Accusation` votes.
+    pub fn our_vote_order(&self) -> Vec<&Observation<T, S::PublicId>> {
+        self.peer_list
+            .our_events()
+            .filter_map(|event_index| self.graph.get(event_index))
+            .filter_map(|event| self.event_payload(event.inner()))
+            .collect()
+    }
+
+    /// Returns all the observations voted for by any peer whose votes are in our gossip graph,
+    /// each together with a flag indicating whether it has been consensused already. This includes
+    /// `Genesis` and `Accusation` observations, which can be told apart by their variant.
+    ///
+    /// If the consensus mode for an observation is `Single`, it is returned once per peer that
+    /// voted for it, as each such vote is consensused separately.
+    pub fn all_observations(&self) -> impl Iterator<Item = (&Observation<T, S::PublicId>, bool)> {
+        self.observations
+            .values()
+            .map(|info| (&info.observation, info.consensused))
+    }
+
+    /// Returns the peers that have forked, each together with the hashes of its fork points, i.e.
+    /// its events which have more than one self-child in our gossip graph. The fork points are
+    /// listed in the order they were inserted into the graph.
+    pub fn forks(&self) -> Vec<(S::PublicId, Vec<EventHash>)> {
+        let mut num_self_children = BTreeMap::new();
+        for event in &self.graph {
+            if let Some(self_parent) = event.self_parent() {
+                *num_self_children.entry(self_parent).or_insert(0) += 1;
+            }
+        }
+
+        let mut forks = BTreeMap::new();
+        for (event_index, _) in num_self_children.into_iter().filter(|(_, num)| *num > 1) {
+            let event = if let Some(event) = self.graph.get(event_index) {
+                event
+            } else {
+                continue;
+            };
+            let peer_id = if let Some(peer) = self.peer_list.get(event.creator()) {
+                peer.id().clone()
+            } else {
+                continue;
+            };
+            forks
+                .entry(peer_id)
+                .or_insert_with(Vec::new)
+                .push(*event.hash());
+        }
+
+        forks.into_iter().collect()
+    }
+
+    /// Checks the invariants of our gossip graph, returning the first violation found, if any.
+    ///
+    /// This is an expensive check meant for tests and health checks, to catch corruption early.
+    pub fn validate_graph(&self) -> result::Result<(), GraphInconsistency> {
+        for event in &self.graph {
+            let hash = *event.hash();
+
+            let self_parent = if let Some(index) = event.self_parent() {
+                let self_parent = self
+                    .graph
+                    .get(index)
+                    .ok_or(GraphInconsistency::MissingSelfParent(hash))?;
+                if self_parent.event_index() >= event.event_index() {
+                    return Err(GraphInconsistency::ParentAfterChild(hash));
+                }
+                if self_parent.index_by_creator() + 1 != event.index_by_creator() {
+                    return Err(GraphInconsistency::InvalidIndexByCreator(hash));
+                }
+                Some(self_parent)
+            } else {
+                if event.index_by_creator() != 0 {
+                    return Err(GraphInconsistency::InvalidIndexByCreator(hash));
+                }
+                None
+            };
+
+            let other_parent = if let Some(index) = event.other_parent() {
+                let other_parent = self
+                    .graph
+                    .get(index)
+                    .ok_or(GraphInconsistency::MissingOtherParent(hash))?;
+                if other_parent.event_index() >= event.event_index() {
+                    return Err(GraphInconsistency::ParentAfterChild(hash));
+                }
+                Some(other_parent)
+            } else {
+                None
+            };
+
+            // The event must be its own creator's last ancestor, and it must know at least as
+            // much about every creator as its parents do.
+            if event.last_ancestor_by(event.creator()) != Some(event.index_by_creator()) {
+                return Err(GraphInconsistency::InvalidLastAncestors(hash));
+            }
+            let parents_consistent = self_parent
+                .into_iter()
+                .chain(other_parent)
+                .flat_map(|parent| parent.inner().last_ancestors())
+                .all(|(peer_index, last)| {
+                    event
+                        .last_ancestor_by(peer_index)
+                        .map_or(false, |our_last| our_last >= last)
+                });
+            if !parents_consistent {
+                return Err(GraphInconsistency::InvalidLastAncestors(hash));
+            }
+
+            if let Some(payload_key) = event.payload_key() {
+                if !self.observations.contains_key(payload_key) {
+                    return Err(GraphInconsistency::MissingObservation(hash));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns, for each voter, statistics about how responsive it is to gossip requests, based on
+    /// the request/response exchanges recorded in our gossip graph. This can help spotting a
+    /// lagging peer before it stalls consensus.
+    pub fn peer_responsiveness(&self) -> BTreeMap<S::PublicId, ResponsivenessStats> {
+        let mut stats: PeerIndexMap<ResponsivenessStats> = self
+            .peer_list
+            .voters()
+            .map(|(peer_index, _)| (peer_index, ResponsivenessStats::default()))
+            .collect();
+
+        // A response event's other-parent is the responder's request event, whose other-parent in
+        // turn is the requester's requesting event.
+        for response in self.graph.iter().filter(|event| event.is_response()) {
+            let request = if let Some(request) = self.graph.other_parent(response) {
+                request
+            } else {
+                continue;
+            };
+            let requesting = if let Some(requesting) = self.graph.other_parent(request) {
+                requesting
+            } else {
+                continue;
+            };
+            if requesting.creator() != response.creator() {
+                continue;
+            }
+
+            if let Some(stats) = stats.get_mut(request.creator()) {
+                stats.add_response(
+                    response
+                        .index_by_creator()
+                        .saturating_sub(requesting.index_by_creator()),
+                );
+            }
+        }
+
+        stats
+            .iter()
+            .filter_map(|(peer_index, stats)| {
+                self.peer_list
+                    .get(peer_index)
+                    .map(|peer| (peer.id().clone(), *stats))
+            })
+            .collect()
+    }
+
+    /// Returns a rough estimate of how much memory this instance holds, broken down by its main
+    /// structures. This is meant for capacity planning rather than exact accounting.
+    pub fn estimated_memory_usage(&self) -> MemoryReport {
+        MemoryReport {
+            graph: self
+                .graph
+                .iter()
+                .map(|event| event.inner().estimated_memory_usage())
+                .sum(),
+            observations: self.observations.len()
+                * mem::size_of::<(ObservationKey, ObservationInfo<T, S::PublicId>)>(),
+            meta_election: self.meta_election.estimated_memory_usage(),
+            consensused_blocks: self
+                .consensused_blocks
+                .iter()
+                .flat_map(|block_group| block_group.iter())
+                .map(Block::estimated_memory_usage)
+                .sum(),
+        }
+    }
+
+    /// Returns the time elapsed since the event with the given hash was inserted into our gossip
+    /// graph, or `None` if we don't know the event.
+    #[cfg(feature = "timing")]
+    pub fn event_age(&self, event_hash: &EventHash) -> Option<Duration> {
+        self.graph
+            .get_index(event_hash)
+            .and_then(|event_index| self.graph.insertion_time(event_index))
+            .map(|insertion_time| insertion_time.elapsed())
+    }
+
+    /// Returns whether consensus seems stuck: there are payloads waiting for consensus, the oldest
+    /// event still carrying one was inserted into our graph at least `since` ago, and for at least
+    /// `since`, the start of the active consensus window (see `active_consensus_window`) hasn't
+    /// advanced.
+    #[cfg(feature = "timing")]
+    pub fn is_stalled(&self, since: Duration) -> bool {
+        let oldest_waiting_since = if let Some(insertion_time) = self
+            .meta_election
+            .unconsensused_events(None)
+            .next()
+            .and_then(|event_index| self.graph.insertion_time(event_index))
+        {
+            insertion_time
+        } else {
+            return false;
+        };
+
+        cmp::max(oldest_waiting_since, self.last_consensus_progress).elapsed() >= since
+    }
+
+    /// Returns the number of blocks per second we created during the last `window`. Only the most
+    /// recent 1,000 blocks are taken into account, so for long windows with high throughput the
+    /// rate is underestimated. Returns `0.0` for an empty `window`.
+    #[cfg(feature = "timing")]
+    pub fn consensus_rate(&self, window: Duration) -> f64 {
+        if window == Duration::from_secs(0) {
+            return 0.0;
+        }
+
+        let num_blocks = self
+            .block_times
+            .iter()
+            .rev()
+            .take_while(|block_time| block_time.elapsed() <= window)
+            .count();
+
+        let window_secs = window.as_secs() as f64 + f64::from(window.subsec_nanos()) / 1e9;
+        num_blocks as f64 / window_secs
+    }
+
+    /// Returns the time it took us to reach consensus on the given block, measured from the
+    /// insertion of the first vote for its payload into our gossip graph until the block was
+    /// created. Returns `None` if the block wasn't created by us, or if it isn't among the most
+    /// recent 1,000 blocks we created.
+    #[cfg(feature = "timing")]
+    pub fn block_consensus_duration(&self, block: &Block<T, S::PublicId>) -> Option<Duration> {
+        let payload = block.payload();
+        let consensus_mode = self.consensus_mode.of(payload);
+        let creator = match consensus_mode {
+            ConsensusMode::Single => block
+                .proofs()
+                .iter()
+                .next()
+                .and_then(|proof| self.peer_list.get_index(proof.public_id()))?,
+            // The creator is not part of the key in these modes.
+            ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => PeerIndex::OUR,
+        };
+        let key = ObservationKey::new(ObservationHash::from(payload), creator, consensus_mode);
+
+        self.consensus_durations
+            .iter()
+            .rev()
+            .find(|(consensus_key, _)| *consensus_key == key)
+            .map(|(_, duration)| *duration)
+    }
+
+    /// Returns the causal relationship of the event with hash `a` to the event with hash `b`, or
+    /// `None` if we don't know either of them.
+    pub fn causal_relation(&self, a: &EventHash, b: &EventHash) -> Option<CausalRelation> {
+        let a = self.graph.get_by_hash(a)?;
+        let b = self.graph.get_by_hash(b)?;
+
+        let relation = if a == b {
+            CausalRelation::Same
+        } else if b.is_descendant_of(a) {
+            CausalRelation::AncestorOf
+        } else if a.is_descendant_of(b) {
+            CausalRelation::DescendantOf
+        } else {
+            CausalRelation::Concurrent
+        };
+        Some(relation)
+    }
+
+    /// Returns information about the meta-event of the event with the given hash in the current
+    /// meta-election, or `None` if we don't know the event or haven't created its meta-event yet.
+    pub fn meta_event_info(&self, event_hash: &EventHash) -> Option<MetaEventInfo<T, S::PublicId>> {
+        let event_index = self.graph.get_index(event_hash)?;
+        let meta_event = self.meta_election.meta_event(event_index)?;
+        let interesting_content = self.resolve_interesting_content(meta_event);
+
+        Some(MetaEventInfo::new(meta_event, interesting_content))
+    }
+
+    /// Exports the meta-votes of all the events of the current meta-election. Comparing the
+    /// snapshots of different peers shows exactly where their views of the meta-election diverged,
+    /// e.g. when consensus stalls.
+    #[cfg(any(test, feature = "testing", feature = "dump-graphs"))]
+    pub fn export_meta_votes(&self) -> MetaVoteSnapshot<S::PublicId> {
+        let meta_votes = self
+            .meta_election
+            .meta_events
+            .iter()
+            .filter_map(|(event_index, meta_event)| {
+                let event_hash = *self.graph.get(*event_index)?.hash();
+                let meta_votes = meta_event
+                    .meta_votes
+                    .iter()
+                    .filter_map(|(peer_index, meta_votes)| {
+                        let peer_id = self.peer_list.get(peer_index)?.id().clone();
+                        Some((peer_id, meta_votes.iter().map(MetaVoteState::new).collect()))
+                    })
+                    .collect();
+                Some((event_hash, meta_votes))
+            })
+            .collect();
+        MetaVoteSnapshot { meta_votes }
+    }
+
+    /// Returns the interesting content of the event with the given hash in the current
+    /// meta-election, in the order in which it was computed for the event's meta-event: the
+    /// payloads voted for by the event's creator come first, in the creator's vote order, followed
+    /// by the payloads the creator hasn't voted for, in an order consistent across all peers.
+    /// Returns `None` if we don't know the event or haven't created its meta-event yet.
+    ///
+    /// This is meant for debugging the order of the consensused blocks.
+    pub fn interesting_content_order(
+        &self,
+        event_hash: &EventHash,
+    ) -> Option<Vec<Observation<T, S::PublicId>>> {
+        let event_index = self.graph.get_index(event_hash)?;
+        let meta_event = self.meta_election.meta_event(event_index)?;
+        Some(self.resolve_interesting_content(meta_event))
+    }
+
+    /// Returns how close `observation` is to consensus in the current meta-election, without
+    /// polling. Under `ConsensusMode::Single`, this is about our own vote for `observation`.
+    pub fn consensus_confidence(
+        &self,
+        observation: &Observation<T, S::PublicId>,
+    ) -> ConsensusConfidence {
+        let hash = ObservationHash::from(observation);
+        let key = ObservationKey::new(hash, PeerIndex::OUR, self.consensus_mode.of(observation));
+
+        let meta_votes = self
+            .peer_list
+            .last_event(PeerIndex::OUR)
+            .and_then(|event_index| self.meta_election.populated_meta_votes(event_index));
+
+        let voters = self.voters();
+        let interesting: Vec<_> = voters
+            .iter()
+            .filter(|peer_index| {
+                self.meta_election
+                    .interesting_content_by(*peer_index)
+                    .map_or(false, |content| content.contains(&key))
+            })
+            .collect();
+        let num_decided = interesting
+            .iter()
+            .filter(|peer_index| {
+                meta_votes
+                    .and_then(|meta_votes| meta_votes.get(**peer_index))
+                    .and_then(|peer_meta_votes| peer_meta_votes.last())
+                    .and_then(MetaVote::decision)
+                    == Some(true)
+            })
+            .count();
+
+        ConsensusConfidence::new(voters.len(), interesting.len(), num_decided)
+    }
+
+    /// Returns the range of topological indices of the gossip graph the current meta-election
+    /// still reasons about, as `(start_index, graph_len)`. `start_index` is the index of the oldest
+    /// event carrying a payload which isn't consensused yet. A wide window means some payload has
+    /// been waiting for consensus for a long time.
+    pub fn active_consensus_window(&self) -> (usize, usize) {
+        (
+            self.meta_election.new_consensus_start_index(),
+            self.graph.len(),
+        )
+    }
+
+    /// Returns the hashes of the observer events of the current meta-election, in topological
+    /// order. An observer is the first event of its creator to strongly see a supermajority of the
+    /// voters. The more observers there are, the closer the meta-election is to a decision.
+    pub fn observers(&self) -> Vec<EventHash> {
+        self.meta_election
+            .observers()
+            .into_iter()
+            .filter_map(|event_index| self.graph.get(event_index))
+            .map(|event| *event.hash())
+            .collect()
+    }
+
+    /// Returns the peers whose interesting events in the current meta-election are strongly-seen by
+    /// the event with the given hash, i.e. its observees. The event is an observer if these are a
+    /// supermajority of the voters and none of its self-ancestors is an observer already. Returns
+    /// `None` if we don't know the event or it isn't part of the current meta-election.
+    pub fn observees(&self, event_hash: &EventHash) -> Option<BTreeSet<S::PublicId>> {
+        let event_index = self.graph.get_index(event_hash)?;
+        let _ = self.meta_election.meta_event(event_index)?;
+        let event = self.graph.get(event_index)?;
+
+        Some(
+            self.compute_observees(event)
+                .iter()
+                .filter_map(|peer_index| self.peer_list.get(peer_index).map(Peer::id))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Returns, for each peer with events in our gossip graph, the hash of the latest of them. If
+    /// a peer forked, its entry is the tip of the fork which reaches furthest from its initial
+    /// event; if several do, the one we received last.
+    pub fn frontier(&self) -> BTreeMap<S::PublicId, EventHash> {
+        self.peer_list
+            .all_ids()
+            .filter_map(|(peer_index, peer_id)| {
+                self.peer_list
+                    .last_event(peer_index)
+                    .and_then(|event_index| self.graph.get(event_index))
+                    .map(|event| (peer_id.clone(), *event.hash()))
+            })
+            .collect()
+    }
+
+    /// Returns, for each peer with events in our gossip graph, the topological index of the latest
+    /// of them (the event `frontier` returns the hash of). Comparing these to the size of our graph
+    /// shows how far behind each peer is.
+    pub fn peer_progress(&self) -> BTreeMap<S::PublicId, usize> {
+        self.peer_list
+            .all_ids()
+            .filter_map(|(peer_index, peer_id)| {
+                self.peer_list
+                    .last_event(peer_index)
+                    .map(|event_index| (peer_id.clone(), event_index.topological_index()))
+            })
+            .collect()
+    }
+
+    /// Returns the number of positions in `peer_id`'s sequence of events at which we know it
+    /// forked, i.e. the indices-by-creator at which we hold more than one of its events. Returns 0
+    /// for unknown peers. This doesn't depend on the `malice-detection` feature.
+    pub fn fork_count(&self, peer_id: &S::PublicId) -> usize {
+        self.peer_list
+            .get_index(peer_id)
+            .and_then(|peer_index| self.peer_list.get(peer_index))
+            .map_or(0, |peer| peer.num_forks())
+    }
+
+    /// Returns the hashes of the events we would send to `peer_id` in our next gossip message to
+    /// it, i.e. those of our events which we think it doesn't know yet, in topological order. No
+    /// event is created, packed or sent.
+    ///
+    /// Returns `Error::UnknownPeer` if we don't know `peer_id`, or `Error::Logic` if we don't have
+    /// any of its events yet, in which case our next message would carry our whole graph.
+    pub fn pending_gossip_for(&self, peer_id: &S::PublicId) -> Result<Vec<EventHash>> {
+        let peer_index = self.get_peer_index(peer_id)?;
+        if self.peer_list.last_event(peer_index).is_none() {
+            return Err(Error::Logic);
+        }
+
+        Ok(self
+            .events_to_gossip_to_peer(peer_index)?
+            .map(|event| *event.hash())
+            .collect())
+    }
+
+    /// Returns the consensused additions and removals of peers, in the order they were
+    /// consensused. The members of the genesis group aren't included, nor are the changes trimmed
+    /// by `trim_consensus_history`.
+    pub fn membership_history(&self) -> Vec<MembershipChange<S::PublicId>> {
+        let num_trimmed = self.meta_election.trimmed_history_len;
+        self.meta_election
+            .consensus_history()
+            .iter()
+            .enumerate()
+            .map(|(position, key)| (num_trimmed + position, key))
+            .filter_map(|(block_index, key)| {
+                match self.observations.get(key).map(|info| &info.observation) {
+                    Some(Observation::Add { peer_id, .. }) => Some(MembershipChange::Add {
+                        peer_id: peer_id.clone(),
+                        block_index,
+                    }),
+                    Some(Observation::Remove { peer_id, .. }) => Some(MembershipChange::Remove {
+                        peer_id: peer_id.clone(),
+                        block_index,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
     }
 
-    /// Returns the next group of stable blocks, if any. The method might need to be called more
-    /// than once for the caller to get all the blocks that have been consensused. A `None` value
-    /// means that all the blocks consensused so far have already been returned.
-    ///
-    /// Once the owning peer has been removed from the section (i.e. a block with payload
-    /// `Observation::Remove(our_id)` has been made stable), then no further blocks will be
-    /// enqueued. So, once `poll()` or `batch_poll()` returns such a block, it will continue to
-    /// return `None` forever.
-    pub(crate) fn batch_poll(&mut self) -> Option<BlockGroup<T, S::PublicId>> {
-        self.consensused_blocks.pop_front()
+    /// Returns the number of consensused accusations, by type of malice. Each accusation is counted
+    /// once, however many peers voted for it. Accusations trimmed by `trim_consensus_history`
+    /// aren't counted.
+    pub fn accusation_stats(&self) -> BTreeMap<MaliceKind, usize> {
+        let mut stats = BTreeMap::new();
+        for key in self.meta_election.consensus_history() {
+            if let Some(Observation::Accusation { malice, .. }) =
+                self.observations.get(key).map(|info| &info.observation)
+            {
+                *stats.entry(malice.kind()).or_insert(0) += 1;
+            }
+        }
+        stats
     }
 
-    /// Check if the owning peer can vote (that is, it has reached a consensus on itself being a
-    /// full member of the section).
-    pub fn can_vote(&self) -> bool {
-        self.peer_list.our_state().can_vote()
+    /// Returns the voters of the meta-election which decided the block carrying the given payload,
+    /// i.e. the section membership at the time that block was consensused. Returns `None` if no
+    /// such payload has been consensused, or if it has been trimmed by `trim_consensus_history`.
+    pub fn voters_for_payload(&self, payload: &ObservationId) -> Option<BTreeSet<S::PublicId>> {
+        let history_index = self.meta_election.trimmed_history_len
+            + self
+                .meta_election
+                .consensus_history()
+                .iter()
+                .position(|key| key.hash() == payload.hash())?;
+        let voters = self.meta_election.voters_at(history_index)?;
+        Some(
+            voters
+                .iter()
+                .filter_map(|peer_index| self.peer_list.get(peer_index))
+                .map(|peer| peer.id().clone())
+                .collect(),
+        )
     }
 
-    /// Checks if the given `observation` has already been voted for by the owning peer.
-    pub fn have_voted_for(&self, observation: &Observation<T, S::PublicId>) -> bool {
-        let hash = ObservationHash::from(observation);
-        let key = ObservationKey::new(hash, PeerIndex::OUR, self.consensus_mode.of(observation));
-        self.observations
-            .get(&key)
-            .map(|info| info.created_by_us)
-            .unwrap_or(false)
+    /// Discards all but the most recent `keep_last` entries of the consensus history, i.e. the
+    /// record of which payload each block carried, to bound the memory used by long-lived
+    /// sections. Returns the number of entries discarded. Block indices are unaffected, but the
+    /// queries based on the history, such as `membership_history` and `voters_for_payload`, no
+    /// longer cover the discarded entries.
+    ///
+    /// No observation can be collected as a result: every observation, trimmed or not, is kept for
+    /// as long as the gossip graph holds an event voting for it, which is for the lifetime of this
+    /// instance. Events refer to their votes' observations, which are needed to gossip them and to
+    /// tell that the payload was consensused already, so that it isn't consensused again.
+    ///
+    /// Returns `Error::ConsensusHistoryInUse` if `keep_last` doesn't cover the active window of the
+    /// history, which starts at the entry of the oldest block still waiting to be returned by
+    /// `poll()`. Internal payloads, such as DKG messages, have entries but no blocks, so the window
+    /// can be longer than `consensused_backlog()`.
+    pub fn trim_consensus_history(&mut self, keep_last: usize) -> Result<usize> {
+        if keep_last < self.active_consensus_window() {
+            return Err(Error::ConsensusHistoryInUse);
+        }
+
+        Ok(self.meta_election.trim_history(keep_last))
     }
 
-    /// Check if there are any observations that have been voted for but not yet polled - that is,
-    /// either they haven't been consensused yet or a block containing that observation hasn't yet
-    /// been retrieved by calling `poll`.
-    pub fn has_unpolled_observations(&self) -> bool {
-        self.observations.values().any(|info| !info.consensused)
-            || !self.consensused_blocks.is_empty()
+    // Number of the latest entries of the consensus history, starting with the entry of the oldest
+    // block still waiting to be polled.
+    fn active_consensus_window(&self) -> usize {
+        self.consensused_blocks
+            .iter()
+            .flatten()
+            .map(|block| block.index() as usize)
+            .min()
+            .map_or(0, |oldest_index| {
+                self.meta_election.consensus_history_len() - oldest_index
+            })
     }
 
-    /// Returns observations voted for by the owning peer which haven't been returned as a stable
-    /// block by `poll` yet.
-    /// This includes observations that are either not yet consensused or that are already
-    /// consensused, but not yet popped out of the consensus queue.
-    ///
-    /// The observations are sorted first by the consensus order, then by the vote order.
-    pub fn our_unpolled_observations(&self) -> impl Iterator<Item = &Observation<T, S::PublicId>> {
-        self.our_consensused_observations()
-            .chain(self.our_unconsensused_observations())
+    fn resolve_interesting_content(
+        &self,
+        meta_event: &MetaEvent,
+    ) -> Vec<Observation<T, S::PublicId>> {
+        meta_event
+            .interesting_content
+            .iter()
+            .filter_map(|key| self.observations.get(key))
+            .map(|info| info.observation.clone())
+            .collect()
     }
 
     fn our_consensused_observations(&self) -> impl Iterator<Item = &Observation<T, S::PublicId>> {
@@ -478,12 +1982,14 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .flatten()
             .filter(|block| block.payload() == payload);
 
-        // In `Supermajority` mode, check only if the payload matches, as there can be blocks not
-        // signed by us, yet with payloads voted for by us.
+        // In `Supermajority` or `Threshold` mode, check only if the payload matches, as there can
+        // be blocks not signed by us, yet with payloads voted for by us.
         // In `Single` mode, on the other hand, check also that we signed it, to avoid false
         // positives when there are blocks with the same payloads but signed by someone else.
         match self.consensus_mode.of(payload) {
-            ConsensusMode::Supermajority => matching_blocks.next().is_some(),
+            ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => {
+                matching_blocks.next().is_some()
+            }
             ConsensusMode::Single => {
                 matching_blocks.any(|block| block.is_signed_by(self.our_pub_id()))
             }
@@ -492,7 +1998,33 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
 
     /// Must only be used for events which have already been added to our graph.
     fn get_known_event(&self, event_index: EventIndex) -> Result<IndexedEventRef<S::PublicId>> {
-        get_known_event(self.our_pub_id(), &self.graph, event_index)
+        get_known_event(
+            self.panic_on_inconsistency,
+            self.our_pub_id(),
+            &self.graph,
+            event_index,
+        )
+    }
+
+    fn confirm_supported_protocol_version(version: u16) -> Result<()> {
+        if version < MIN_SUPPORTED_PROTOCOL_VERSION || version > PROTOCOL_VERSION {
+            Err(Error::UnsupportedProtocolVersion { version })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn confirm_consensused_backlog_not_full(&self) -> Result<()> {
+        match self.max_consensused_backlog {
+            Some(limit) if self.num_consensused_blocks() > limit => {
+                debug!(
+                    "{:?} has too many consensused blocks waiting to be polled",
+                    self.our_pub_id()
+                );
+                Err(Error::ConsensusBacklogFull)
+            }
+            _ => Ok(()),
+        }
     }
 
     fn confirm_allowed_to_gossip_to(&self, peer_index: PeerIndex) -> Result<()> {
@@ -563,13 +2095,67 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         })
     }
 
+    // Checks that the event's self-parent is by its creator and its other-parent by someone else.
+    // With `malice-detection` such events are accused of earlier, but the check is needed in every
+    // build, as the peer list relies on it to keep each peer's events in sequence.
+    fn confirm_consistent_parents(&self, event: &Event<S::PublicId>) -> Result<()> {
+        let bad_self_parent = self.graph.self_parent(event).map_or(false, |self_parent| {
+            self_parent.creator() != event.creator()
+        });
+        let bad_other_parent = self
+            .graph
+            .other_parent(event)
+            .map_or(false, |other_parent| {
+                other_parent.creator() == event.creator()
+            });
+
+        if bad_self_parent || bad_other_parent {
+            debug!(
+                "{:?} received event {:?} with inconsistent parents",
+                self.our_pub_id(),
+                event
+            );
+            Err(Error::InvalidEvent)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Checks that the event's payload passes `NetworkEvent::validate` and doesn't exceed our
+    // maximum observation size. With `malice-detection` the creator of such an event is accused
+    // earlier, but the event must be rejected in every build, so that it can't get consensused.
+    fn confirm_valid_payload(&self, event: &Event<S::PublicId>) -> Result<()> {
+        let is_valid = self.event_payload(event).map_or(true, |observation| {
+            observation.opaque_payloads().iter().all(T::validate)
+                && observation.serialised_size() <= self.max_observation_size
+        });
+
+        if is_valid {
+            Ok(())
+        } else {
+            debug!(
+                "{:?} received event {:?} with invalid or oversized payload",
+                self.our_pub_id(),
+                event
+            );
+            Err(Error::InvalidEvent)
+        }
+    }
+
+    fn confirm_can_handle_gossip_from(&self, src_index: PeerIndex) -> Result<()> {
+        self.confirm_consensused_backlog_not_full()?;
+        self.confirm_self_state(PeerState::RECV)?;
+        self.confirm_peer_state(src_index, PeerState::SEND)
+    }
+
     fn get_peer_index(&self, peer_id: &S::PublicId) -> Result<PeerIndex> {
         self.peer_list.get_index(peer_id).ok_or(Error::UnknownPeer)
     }
 
     fn our_last_event_index(&self) -> Result<EventIndex> {
         self.peer_list.last_event(PeerIndex::OUR).ok_or_else(|| {
-            log_or_panic!(
+            log_or_panic_if!(
+                self.panic_on_inconsistency,
                 "{:?} has no last event.\n{:?}\n",
                 self.our_pub_id(),
                 self.peer_list
@@ -589,6 +2175,36 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .collect()
     }
 
+    // We no longer process gossip once we've been removed from the section, but still answer
+    // requests with the events the requester may lack, so it can learn of our removal from them.
+    // Our own events created after the removal are left out, as peers aware of it reject them.
+    fn respond_after_removal(&self, src_index: PeerIndex) -> Result<Response<T, S::PublicId>> {
+        debug!(
+            "{:?} responding to {:?} after our removal",
+            self.our_pub_id(),
+            src_index
+        );
+
+        let removal_event = self
+            .peer_list
+            .get(PeerIndex::OUR)
+            .and_then(Peer::removal_event)
+            .and_then(|event_index| self.graph.get(event_index));
+        let is_after_removal = |event: &Event<S::PublicId>| {
+            event.creator() == PeerIndex::OUR
+                && removal_event
+                    .map_or(false, |removal_event| event.is_descendant_of(removal_event))
+        };
+
+        let events: Vec<_> = if self.peer_list.last_event(src_index).is_some() {
+            self.events_to_gossip_to_peer(src_index)?.collect()
+        } else {
+            self.graph.iter().map(|event| event.inner()).collect()
+        };
+        let events = events.into_iter().filter(|event| !is_after_removal(*event));
+        self.pack_events(events).map(Response::new)
+    }
+
     // Returns the list peers which have created forked events, and the event to use as the
     // other-parent when creating our sync event as a result of handling this message.
     fn unpack_and_add_events(
@@ -596,28 +2212,57 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         src_index: PeerIndex,
         packed_events: Vec<PackedEvent<T, S::PublicId>>,
     ) -> Result<EventIndex> {
-        self.confirm_self_state(PeerState::RECV)?;
-        self.confirm_peer_state(src_index, PeerState::SEND)?;
+        self.confirm_can_handle_gossip_from(src_index)?;
+
+        if packed_events.len() > self.max_incoming_events() {
+            debug!(
+                "{:?} received gossip with too many events ({}) from {:?}",
+                self.our_pub_id(),
+                packed_events.len(),
+                src_index
+            );
+            return Err(Error::GossipTooLarge);
+        }
+
+        #[cfg(feature = "malice-detection")]
+        self.detect_stale_gossip(src_index, &packed_events);
 
         let hash_of_last_event = packed_events
             .last()
             .map(PackedEvent::compute_hash)
             .ok_or_else(|| Error::InvalidMessage)?;
-        for packed_event in packed_events {
-            if let Some(event) = self.unpack(packed_event)? {
-                let event_creator = event.creator();
-                let event_index = self.add_event(event)?;
 
-                // We have received an event of a peer in the message. The peer can now receive
-                // gossips from us as well.
-                self.peer_list
-                    .change_peer_state(event_creator, PeerState::RECV);
-                self.peer_list
-                    .record_gossiped_event_by(src_index, event_index);
+        // While we're still joining, we may not know all the members of the section yet. Events
+        // by peers we haven't learnt about (and their descendants) are deferred until the events
+        // preceding them let us reach consensus on adding those peers. Deferred events keep the
+        // hash computed when verifying their signature, so retrying them doesn't verify it again.
+        let joining = !self.peer_list.our_state().can_vote();
+        let mut deferred = vec![];
+        for packed_event in packed_events {
+            let hash = packed_event.verify_signature()?;
+            if joining {
+                self.unpack_and_add_or_defer(src_index, hash, packed_event, &mut deferred)?;
+            } else if let Some(event) = self.unpack_received(src_index, hash, packed_event)? {
+                self.add_received_event(src_index, event)?;
+            }
+        }
 
-                #[cfg(feature = "malice-detection")]
-                self.detect_accomplice(event_index)?;
+        while !deferred.is_empty() {
+            let num_deferred = deferred.len();
+            let mut still_deferred = vec![];
+            for (hash, packed_event) in deferred {
+                self.unpack_and_add_or_defer(src_index, hash, packed_event, &mut still_deferred)?;
+            }
+            if still_deferred.len() == num_deferred {
+                debug!(
+                    "{:?} couldn't add {} events from {:?} created by peers unknown to us yet",
+                    self.our_pub_id(),
+                    num_deferred,
+                    src_index
+                );
+                return Err(Error::UnknownPeer);
             }
+            deferred = still_deferred;
         }
 
         #[cfg(feature = "malice-detection")]
@@ -630,11 +2275,125 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Ok(last_event_index)
     }
 
+    fn add_received_event(
+        &mut self,
+        src_index: PeerIndex,
+        event: Event<S::PublicId>,
+    ) -> Result<()> {
+        let event_creator = event.creator();
+        if event_creator == PeerIndex::OUR {
+            // We hold every event we ever created, so one we don't know yet can't be ours.
+            debug!(
+                "{:?} received unknown event {:?} claiming to be ours",
+                self.our_pub_id(),
+                event
+            );
+            return Err(Error::InvalidEvent);
+        }
+        let event_index = self.add_event(event)?;
+
+        // We have received an event of a peer in the message. The peer can now receive
+        // gossips from us as well.
+        self.peer_list
+            .change_peer_state(event_creator, PeerState::RECV)?;
+        self.peer_list
+            .record_gossiped_event_by(src_index, event_index)?;
+
+        #[cfg(feature = "malice-detection")]
+        {
+            self.detect_accomplice(event_index)?;
+            self.confirm_pending_accusations_within_limit()?;
+        }
+
+        Ok(())
+    }
+
+    // Defers the event if it is by a peer unknown to us or if any of its parents is deferred.
+    // Any other failure to unpack or add it is returned as is.
+    fn unpack_and_add_or_defer(
+        &mut self,
+        src_index: PeerIndex,
+        hash: EventHash,
+        packed_event: PackedEvent<T, S::PublicId>,
+        deferred: &mut Vec<(EventHash, PackedEvent<T, S::PublicId>)>,
+    ) -> Result<()> {
+        let has_deferred_parent = packed_event.parent_hashes().any(|parent_hash| {
+            deferred
+                .iter()
+                .any(|(deferred_hash, _)| deferred_hash == parent_hash)
+        });
+        if has_deferred_parent {
+            deferred.push((hash, packed_event));
+            return Ok(());
+        }
+
+        match self.unpack_received(src_index, hash, packed_event.clone()) {
+            Ok(Some(event)) => self.add_received_event(src_index, event),
+            Ok(None) => Ok(()),
+            Err(Error::UnknownPeer) => {
+                deferred.push((hash, packed_event));
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Returns whether we already created a sync event with the given other-parent.
+    fn have_synced_with(&self, other_parent: EventIndex) -> bool {
+        self.peer_list.our_events().rev().any(|event_index| {
+            self.graph
+                .get(event_index)
+                .and_then(|event| event.other_parent())
+                == Some(other_parent)
+        })
+    }
+
+    fn max_incoming_events(&self) -> usize {
+        self.max_incoming_events.unwrap_or_else(|| {
+            cmp::max(
+                MIN_INCOMING_EVENTS_LIMIT,
+                INCOMING_EVENTS_LIMIT_FACTOR.saturating_mul(self.graph.len()),
+            )
+        })
+    }
+
+    // Like `unpack`, but for an event received from `src_index`, whom we accuse if the event is
+    // malformed.
+    fn unpack_received(
+        &mut self,
+        src_index: PeerIndex,
+        hash: EventHash,
+        packed_event: PackedEvent<T, S::PublicId>,
+    ) -> Result<Option<Event<S::PublicId>>> {
+        let result = self.unpack_verified(hash, packed_event);
+        #[cfg(feature = "malice-detection")]
+        {
+            if let Err(Error::MalformedEvent) = result {
+                self.accuse(src_index, Malice::Unprovable(UnprovableMalice::Unspecified));
+            }
+        }
+        #[cfg(not(feature = "malice-detection"))]
+        let _ = src_index;
+        result
+    }
+
     fn unpack(
         &mut self,
         packed_event: PackedEvent<T, S::PublicId>,
     ) -> Result<Option<Event<S::PublicId>>> {
-        if let Some(unpacked_event) = Event::unpack(packed_event, self.event_context())? {
+        let hash = packed_event.verify_signature()?;
+        self.unpack_verified(hash, packed_event)
+    }
+
+    // Like `unpack`, but for an event whose signature has already been verified.
+    fn unpack_verified(
+        &mut self,
+        hash: EventHash,
+        packed_event: PackedEvent<T, S::PublicId>,
+    ) -> Result<Option<Event<S::PublicId>>> {
+        if let Some(unpacked_event) =
+            Event::unpack_verified(packed_event, hash, self.event_context())?
+        {
             if let Some((payload_key, observation_info)) = unpacked_event.observation_for_store {
                 let _ = self
                     .observations
@@ -666,16 +2425,30 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     }
 
     fn add_event(&mut self, event: Event<S::PublicId>) -> Result<EventIndex> {
-        let our = event.creator() == PeerIndex::OUR;
-        if !our {
-            #[cfg(feature = "malice-detection")]
-            self.detect_malice(&event)?;
+        #[cfg(feature = "malice-detection")]
+        {
+            if event.creator() != PeerIndex::OUR {
+                self.detect_malice(&event)?;
+            }
         }
 
+        self.add_event_without_malice_detection(event)
+    }
+
+    fn add_event_without_malice_detection(
+        &mut self,
+        event: Event<S::PublicId>,
+    ) -> Result<EventIndex> {
+        let our = event.creator() == PeerIndex::OUR;
         self.confirm_can_add_event(&event)?;
+        self.confirm_consistent_parents(&event)?;
+        if !our {
+            self.confirm_valid_payload(&event)?;
+        }
 
         if our && event.is_initial() {
-            log_or_panic!(
+            log_or_panic_if!(
+                self.panic_on_inconsistency,
                 "{:?} attempted to add initial event with add_event. It must be added with add_initial_event instead.",
                 self.our_pub_id(),
             );
@@ -696,7 +2469,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 }
             });
 
-        let event_index = self.insert_event(event);
+        let event_index = self.insert_event(event)?;
 
         let _ = unconsensused_payload_key.map(|payload_key| {
             self.meta_election
@@ -722,10 +2495,15 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         let _ = self.insert_event(event);
     }
 
-    fn insert_event(&mut self, event: Event<S::PublicId>) -> EventIndex {
+    fn insert_event(&mut self, event: Event<S::PublicId>) -> Result<EventIndex> {
         let event = self.graph.insert(event);
-        self.peer_list.add_event(event);
-        event.event_index()
+        let event_index = event.event_index();
+        self.peer_list.add_event(event)?;
+
+        #[cfg(feature = "malice-detection")]
+        self.record_accusation(event_index);
+
+        Ok(event_index)
     }
 
     fn process_events(&mut self, mut start_index: usize) -> Result<()> {
@@ -755,6 +2533,9 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
 
         self.create_needed_meta_event(event_index)?;
 
+        // All the payloads decided in the current meta-election are consensused together, in the
+        // same deterministic order on every node (see `compute_payloads_for_consensus`), and
+        // emitted as a single group of blocks. Only then is a fresh meta-election started.
         let payload_keys = self.compute_consensus(event_index);
         if payload_keys.is_empty() {
             return Ok(PostProcessAction::Continue);
@@ -762,26 +2543,76 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
 
         self.output_consensus_info(&payload_keys);
 
-        let blocks = self.create_blocks(&payload_keys)?;
+        let mut blocks = self.create_blocks(&payload_keys)?;
+        let min_block_index = self.min_block_index;
+        blocks.retain(|block| block.index() >= min_block_index);
         if !blocks.is_empty() {
+            #[cfg(feature = "timing")]
+            self.record_block_times(blocks.len());
+
             self.consensused_blocks.push_back(blocks);
         }
 
+        #[cfg(feature = "timing")]
+        self.record_consensus_durations(&payload_keys);
+
         self.mark_observations_as_consensused(&payload_keys);
 
+        let history_len = self.meta_election.consensus_history_len();
         let peer_list_changes = payload_keys
             .iter()
-            .filter_map(|payload_key| self.handle_consensus(event_index, payload_key))
+            .enumerate()
+            .filter_map(|(position, payload_key)| {
+                self.handle_consensus(event_index, payload_key, (history_len + position) as u64)
+            })
             .collect();
 
+        #[cfg(feature = "timing")]
+        let old_start_index = self.meta_election.new_consensus_start_index();
+
         self.meta_election
-            .new_election(&self.graph, payload_keys, peer_list_changes);
+            .new_election(&self.graph, payload_keys, peer_list_changes)?;
+
+        #[cfg(feature = "timing")]
+        {
+            if self.meta_election.new_consensus_start_index() != old_start_index {
+                self.last_consensus_progress = Instant::now();
+            }
+        }
 
         // Trigger reprocess.
         let start_index = self.meta_election.continue_consensus_start_index();
         Ok(PostProcessAction::Restart(start_index))
     }
 
+    #[cfg(feature = "timing")]
+    fn record_consensus_durations(&mut self, payload_keys: &[ObservationKey]) {
+        for payload_key in payload_keys {
+            let first_vote_time = self
+                .unconsensused_events(Some(payload_key))
+                .filter_map(|event| self.graph.insertion_time(event.event_index()))
+                .min();
+            if let Some(first_vote_time) = first_vote_time {
+                if self.consensus_durations.len() == CONSENSUS_DURATIONS_CAPACITY {
+                    let _ = self.consensus_durations.pop_front();
+                }
+                self.consensus_durations
+                    .push_back((*payload_key, first_vote_time.elapsed()));
+            }
+        }
+    }
+
+    #[cfg(feature = "timing")]
+    fn record_block_times(&mut self, num_blocks: usize) {
+        let now = Instant::now();
+        for _ in 0..num_blocks {
+            if self.block_times.len() == BLOCK_TIMES_CAPACITY {
+                let _ = self.block_times.pop_front();
+            }
+            self.block_times.push_back(now);
+        }
+    }
+
     fn output_consensus_info(&self, payload_keys: &[ObservationKey]) {
         dump_graph::to_file(dump_graph::ToFileInfo {
             owner_id: self.our_pub_id(),
@@ -792,6 +2623,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             observations: &self.observations,
             secure_rng: &self.secure_rng,
             key_gens_and_next_id: (&self.key_gen, &self.key_gen_next_id),
+            dump_dir: self.dump_dir.as_ref().map(PathBuf::as_path),
             info: &dump_graph::DumpGraphContext::ConsensusReached,
         });
 
@@ -803,7 +2635,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             info!(
                 "{:?} got consensus on block {} with payload {:?} and payload hash {:?}",
                 self.our_pub_id(),
-                self.meta_election.consensus_history().len() + index,
+                self.meta_election.consensus_history_len() + index,
                 payload,
                 payload_key.hash()
             )
@@ -815,7 +2647,8 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             if let Some(info) = self.observations.get_mut(payload_key) {
                 info.consensused = true;
             } else {
-                log_or_panic!(
+                log_or_panic_if!(
+                    self.panic_on_inconsistency,
                     "{:?} doesn't know about observation with hash {:?}",
                     self.peer_list.our_pub_id(),
                     payload_key.hash()
@@ -825,19 +2658,21 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     }
 
     /// Handles consensus reached by us.
+    // `block_index` is the position of the payload in the consensus history.
     fn handle_consensus(
         &mut self,
         event_index: EventIndex,
         payload_key: &ObservationKey,
+        block_index: u64,
     ) -> Option<PeerListChange> {
         match self
             .observations
             .get(payload_key)
             .map(|info| info.observation.clone())
         {
-            Some(Observation::Add { ref peer_id, .. }) => self.handle_add_peer(peer_id).into(),
+            Some(Observation::Add { ref peer_id, .. }) => self.handle_add_peer(peer_id),
             Some(Observation::Remove { ref peer_id, .. }) => {
-                self.handle_remove_peer(event_index, peer_id)
+                self.handle_remove_peer(event_index, peer_id, payload_key)
             }
             Some(Observation::Accusation {
                 ref offender,
@@ -850,7 +2685,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                     malice
                 );
 
-                self.handle_remove_peer(event_index, offender)
+                self.handle_remove_peer(event_index, offender, payload_key)
             }
             Some(Observation::StartDkg(peers)) => {
                 if self.handle_dkg_start_consensus(&peers).is_none() {
@@ -859,11 +2694,17 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 None
             }
             Some(Observation::DkgResult { .. }) => {
-                log_or_panic!("Unexpected DkgResult consensus.");
+                log_or_panic_if!(
+                    self.panic_on_inconsistency,
+                    "Unexpected DkgResult consensus."
+                );
                 None
             }
             Some(Observation::DkgMessage(msg)) => {
-                if self.handle_dkg_message(payload_key, msg.clone()).is_none() {
+                if self
+                    .handle_dkg_message(payload_key, msg.clone(), block_index)
+                    .is_none()
+                {
                     warn!(
                         "Ignoring DkgMessage with Error: key: {:?}, msg: {:?}",
                         payload_key, msg
@@ -871,15 +2712,26 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 }
                 None
             }
-            Some(Observation::Genesis { .. }) | Some(Observation::OpaquePayload(_)) => None,
+            Some(Observation::Genesis { .. })
+            | Some(Observation::OpaquePayload(_))
+            | Some(Observation::Batch(_))
+            | Some(Observation::NoOp(_)) => None,
             None => {
-                log_or_panic!("Failed to get observation from hash.");
+                log_or_panic_if!(
+                    self.panic_on_inconsistency,
+                    "Failed to get observation from hash."
+                );
                 None
             }
         }
     }
 
-    fn handle_dkg_message(&mut self, payload_key: &ObservationKey, msg: DkgMessage) -> Option<()> {
+    fn handle_dkg_message(
+        &mut self,
+        payload_key: &ObservationKey,
+        msg: DkgMessage,
+        block_index: u64,
+    ) -> Option<()> {
         let creator_id = self.peer_list.get(payload_key.peer_index()?)?.id().clone();
 
         match msg {
@@ -887,7 +2739,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 self.handle_dkg_message_part(&creator_id, key_gen_id, part)
             }
             DkgMessage::Ack { key_gen_id, ack } => {
-                self.handle_dkg_message_ack(&creator_id, key_gen_id, ack)
+                self.handle_dkg_message_ack(&creator_id, key_gen_id, ack, block_index)
             }
         }
     }
@@ -927,6 +2779,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         creator_id: &S::PublicId,
         key_gen_id: KeyGenId,
         ack: Ack,
+        block_index: u64,
     ) -> Option<()> {
         if let Some(key_gen) = &mut self.key_gen.get_mut(&key_gen_id) {
             let ack_result = key_gen
@@ -944,9 +2797,10 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                         );
                         let dkg_result = key_gen.generate().ok()?;
 
-                        self.consensused_blocks.push_back(BlockGroup(
-                            iter::once(Block::new_dkg_block(dkg_result)).collect(),
-                        ));
+                        let mut block = Block::new_dkg_block(dkg_result);
+                        block.set_index(block_index);
+                        self.consensused_blocks
+                            .push_back(BlockGroup(iter::once(block).collect()));
                         let _ = self.key_gen.remove(&key_gen_id);
                     }
                 }
@@ -1004,15 +2858,17 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Some(())
     }
 
-    fn handle_add_peer(&mut self, peer_id: &S::PublicId) -> PeerListChange {
+    fn handle_add_peer(&mut self, peer_id: &S::PublicId) -> Option<PeerListChange> {
         let state = if self.new_peer_can_recv(peer_id) {
             PeerState::VOTE | PeerState::SEND | PeerState::RECV
         } else {
             PeerState::VOTE | PeerState::SEND
         };
 
-        let peer_index = self.add_gossip_peer(peer_id, state);
-        PeerListChange::Add(peer_index)
+        let old_state = self.peer_state(peer_id);
+        let peer_index = self.add_gossip_peer(peer_id, state)?;
+        self.notify_peer_state_change(peer_index, old_state);
+        Some(PeerListChange::Add(peer_index))
     }
 
     fn new_peer_can_recv(&self, peer_id: &S::PublicId) -> bool {
@@ -1040,34 +2896,68 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             })
     }
 
-    fn add_gossip_peer(&mut self, peer_id: &S::PublicId, state: PeerState) -> PeerIndex {
+    fn add_gossip_peer(&mut self, peer_id: &S::PublicId, state: PeerState) -> Option<PeerIndex> {
         let peer_index = if let Some(peer_index) = self.peer_list.get_index(peer_id) {
-            self.peer_list.change_peer_state(peer_index, state);
+            self.peer_list.change_peer_state(peer_index, state).ok()?;
             peer_index
         } else {
-            self.peer_list.add_peer(peer_id.clone(), state)
+            self.peer_list.add_peer(peer_id.clone(), state).ok()?
         };
 
-        if peer_index == PeerIndex::OUR && self.peer_list.our_events().next().is_none() {
+        if peer_index == PeerIndex::OUR
+            && !self.observer_only
+            && self.peer_list.our_events().next().is_none()
+        {
             self.add_initial_event();
         }
 
-        peer_index
+        Some(peer_index)
     }
 
     fn handle_remove_peer(
         &mut self,
         event_index: EventIndex,
         peer_id: &S::PublicId,
+        payload_key: &ObservationKey,
     ) -> Option<PeerListChange> {
-        self.peer_list.get_index(peer_id).map(|peer_index| {
-            self.peer_list.remove_peer(peer_index, event_index);
-            PeerListChange::Remove(peer_index)
-        })
+        let peer_index = self.peer_list.get_index(peer_id)?;
+        let old_state = self.peer_state(peer_id);
+        self.peer_list.remove_peer(peer_index, event_index).ok()?;
+        self.meta_election.record_removal(peer_index, *payload_key);
+        self.notify_peer_state_change(peer_index, old_state);
+        Some(PeerListChange::Remove(peer_index))
+    }
+
+    // Returns the state of the given peer, or `PeerState::inactive()` if we don't know it.
+    fn peer_state(&self, peer_id: &S::PublicId) -> PeerState {
+        self.peer_list
+            .get_index(peer_id)
+            .and_then(|peer_index| self.peer_list.get(peer_index))
+            .map_or_else(PeerState::inactive, Peer::state)
+    }
+
+    fn notify_peer_state_change(&mut self, peer_index: PeerIndex, old_state: PeerState) {
+        let peer = if let Some(peer) = self.peer_list.get(peer_index) {
+            peer
+        } else {
+            return;
+        };
+        let new_state = peer.state();
+        if new_state == old_state {
+            return;
+        }
+        if let Some(ref mut callback) = self.on_peer_state_change {
+            callback(peer.id(), old_state, new_state);
+        }
     }
 
     fn create_needed_meta_event(&mut self, event_index: EventIndex) -> Result<()> {
-        let event = get_known_event(self.our_pub_id(), &self.graph, event_index)?;
+        let event = get_known_event(
+            self.panic_on_inconsistency,
+            self.our_pub_id(),
+            &self.graph,
+            event_index,
+        )?;
 
         if !event.is_sync_event() || !self.voters().contains(event.creator()) {
             // Only add meta events for sync events created by a valid voter.
@@ -1121,6 +3011,12 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             self.is_interesting_payload(builder, &peers_that_can_vote, payload_key)
         };
 
+        let priority = |payload_key: &ObservationKey| {
+            self.observations
+                .get(payload_key)
+                .map_or(0, |info| info.observation.priority())
+        };
+
         let payloads = find_interesting_content_for_event(
             builder.event(),
             self.unconsensused_events(None),
@@ -1128,11 +3024,56 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             is_descendant,
             is_already_interesting_content,
             is_interesting_payload,
+            priority,
         );
+        let payloads = if let Some(peer_quota) = self.vote_rate_limit {
+            let proposer = |payload_key: &ObservationKey| {
+                self.opaque_payload_proposer(builder.event(), payload_key)
+            };
+            defer_payloads_over_quota(payloads, proposer, peer_quota)
+        } else {
+            payloads
+        };
 
         builder.set_interesting_content(payloads);
     }
 
+    // The creator of the earliest vote for the given opaque payload among the ancestors of the
+    // given event. If several votes are concurrent, the creator with the lowest public ID is
+    // returned. Returns `None` if the payload isn't opaque.
+    fn opaque_payload_proposer(
+        &self,
+        event: IndexedEventRef<S::PublicId>,
+        payload_key: &ObservationKey,
+    ) -> Option<PeerIndex> {
+        let is_opaque = self
+            .observations
+            .get(payload_key)
+            .map_or(false, |info| info.observation.is_opaque());
+        if !is_opaque {
+            return None;
+        }
+
+        let votes = self
+            .unconsensused_events(Some(payload_key))
+            .filter(|vote| event.is_descendant_of(vote))
+            .collect_vec();
+        votes
+            .iter()
+            .filter(|vote| {
+                !votes.iter().any(|other| {
+                    other.event_index() != vote.event_index() && vote.is_descendant_of(other)
+                })
+            })
+            .filter_map(|vote| {
+                self.peer_list
+                    .get(vote.creator())
+                    .map(|peer| (peer.id(), vote.creator()))
+            })
+            .min_by(|(lhs_id, _), (rhs_id, _)| lhs_id.cmp(rhs_id))
+            .map(|(_, peer_index)| peer_index)
+    }
+
     // Returns true if enough of `valid_voters` have voted for the indicated payload from the
     // perspective of `builder.event()`.
     fn is_interesting_payload(
@@ -1148,13 +3089,31 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 is_more_than_two_thirds(num_ancestor_peers, peers_that_can_vote.len())
                     && self.has_ancestor_carrying_payload(builder.event(), payload_key)
             }
-            ConsensusMode::Supermajority => {
-                let num_peers_that_did_vote = self.num_creators_of_ancestors_carrying_payload(
-                    peers_that_can_vote,
-                    builder.event(),
-                    payload_key,
-                );
-                is_more_than_two_thirds(num_peers_that_did_vote, peers_that_can_vote.len())
+            ConsensusMode::Supermajority | ConsensusMode::Threshold { .. } => {
+                let peers_that_did_vote =
+                    self.creators_of_ancestors_carrying_payload(builder.event(), payload_key);
+                // Peers removed since casting their votes count against the voters of the
+                // meta-election they voted in, so they count towards the voters in total too.
+                let num_peers_that_can_vote = peers_that_can_vote.len()
+                    + peers_that_did_vote
+                        .iter()
+                        .filter(|peer_index| !peers_that_can_vote.contains(*peer_index))
+                        .count();
+                // Only opaque payloads may use a custom threshold. Internal observations always
+                // require a supermajority (see `ConsensusMode::of`), so the payload only needs
+                // looking up if we use a custom threshold.
+                let consensus_mode = match self.consensus_mode {
+                    ConsensusMode::Threshold { .. } => self
+                        .observations
+                        .get(payload_key)
+                        .map_or(ConsensusMode::Supermajority, |info| {
+                            self.consensus_mode.of(&info.observation)
+                        }),
+                    ConsensusMode::Single | ConsensusMode::Supermajority => {
+                        ConsensusMode::Supermajority
+                    }
+                };
+                consensus_mode.check(peers_that_did_vote.len(), num_peers_that_can_vote)
             }
         }
     }
@@ -1171,34 +3130,32 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .count()
     }
 
-    // Number of unique peers that created at least one ancestor of the given event that carries the
-    // given payload.
-    fn num_creators_of_ancestors_carrying_payload(
+    // Unique peers that created at least one ancestor of the given event that carries the given
+    // payload while being a voter (see `was_voter_when_created`).
+    fn creators_of_ancestors_carrying_payload(
         &self,
-        peers_that_can_vote: &PeerIndexSet,
         event: IndexedEventRef<S::PublicId>,
         payload_key: &ObservationKey,
-    ) -> usize {
-        let unconsensused_events = self.unconsensused_events(Some(payload_key)).collect_vec();
-
-        peers_that_can_vote
-            .iter()
-            .filter(|peer_index| {
-                unconsensused_events.iter().any(|that_event| {
-                    that_event.creator() == *peer_index && event.is_descendant_of(*that_event)
-                })
+    ) -> PeerIndexSet {
+        self.unconsensused_events(Some(payload_key))
+            .filter(|that_event| {
+                event.is_descendant_of(*that_event) && self.was_voter_when_created(that_event)
             })
-            .count()
+            .map(|that_event| that_event.creator())
+            .collect()
     }
 
-    // At least one ancestor of the given event carries the given payload.
+    // At least one ancestor of the given event carries the given payload, and its creator was a
+    // voter when creating it (see `was_voter_when_created`).
     fn has_ancestor_carrying_payload(
         &self,
         event: IndexedEventRef<S::PublicId>,
         payload_key: &ObservationKey,
     ) -> bool {
         self.unconsensused_events(Some(payload_key))
-            .any(|that_event| event.is_descendant_of(that_event))
+            .any(|that_event| {
+                event.is_descendant_of(that_event) && self.was_voter_when_created(&that_event)
+            })
     }
 
     fn set_observer(&self, builder: &mut MetaEventBuilder<S::PublicId>) {
@@ -1211,25 +3168,30 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         }
 
         let voter_count = self.voter_count();
-        let observees: PeerIndexSet = self
-            .meta_election
+        let observees = self.compute_observees(builder.event());
+
+        if is_more_than_two_thirds(observees.len(), voter_count) {
+            builder.set_observer(Observer::This(observees));
+        } else {
+            builder.set_observer(Observer::None);
+        }
+    }
+
+    // Peers whose first interesting event in the current meta-election is strongly-seen by the
+    // given event.
+    fn compute_observees(&self, event: IndexedEventRef<S::PublicId>) -> PeerIndexSet {
+        self.meta_election
             .interesting_events()
             .filter_map(|(peer_index, event_indices)| {
                 let event_index = event_indices.first()?;
-                let event = self.get_known_event(*event_index).ok()?;
-                if self.strongly_sees(builder.event(), event) {
+                let interesting_event = self.get_known_event(*event_index).ok()?;
+                if self.strongly_sees(&event, interesting_event) {
                     Some(peer_index)
                 } else {
                     None
                 }
             })
-            .collect();
-
-        if is_more_than_two_thirds(observees.len(), voter_count) {
-            builder.set_observer(Observer::This(observees));
-        } else {
-            builder.set_observer(Observer::None);
-        }
+            .collect()
     }
 
     fn is_descendant_of_observer(&self, event: IndexedEventRef<S::PublicId>) -> bool {
@@ -1258,7 +3220,11 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         let voters_len = match NonZeroUsize::new(voters.len()) {
             Some(num) => num,
             None => {
-                log_or_panic!("{:?} has no voters", self.our_pub_id());
+                log_or_panic_if!(
+                    self.panic_on_inconsistency,
+                    "{:?} has no voters",
+                    self.our_pub_id()
+                );
                 return Ok(());
             }
         };
@@ -1296,6 +3262,28 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 let coin_tosses = self.toss_coins(&voters, peer_index, temp_votes)?;
                 let final_meta_votes = MetaVote::next_final(temp_votes, &coin_tosses, voters_len);
 
+                // Only log the meta-vote state once: when the limit is first exceeded.
+                let exceeds_max_rounds = |meta_votes: &[MetaVote]| {
+                    meta_votes
+                        .iter()
+                        .any(|meta_vote| meta_vote.round > self.max_rounds)
+                };
+                let parent_exceeded_max_rounds = parent_meta_votes
+                    .get(peer_index)
+                    .map_or(false, |votes| exceeds_max_rounds(votes));
+                if exceeds_max_rounds(&final_meta_votes) && !parent_exceeded_max_rounds {
+                    error!(
+                        "{:?} exceeded {} meta-voting rounds at {:?}. Parent meta-votes: {:?}, \
+                         final meta-votes of {:?}: {:?}",
+                        self.our_pub_id(),
+                        self.max_rounds,
+                        *builder.event(),
+                        parent_meta_votes,
+                        peer_index,
+                        final_meta_votes
+                    );
+                }
+
                 builder.add_meta_votes(peer_index, final_meta_votes);
             }
         } else {
@@ -1342,6 +3330,17 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         _peer_index: PeerIndex,
         temp_vote: &MetaVote,
     ) -> Result<Option<bool>> {
+        #[cfg(any(test, feature = "testing"))]
+        {
+            let coin = self.coin_oracle.as_ref().and_then(|oracle| {
+                let peer_id = self.peer_list.get(_peer_index)?.id();
+                oracle(temp_vote.round, peer_id)
+            });
+            if coin.is_some() {
+                return Ok(coin);
+            }
+        }
+
         // FIXME: This coin is common, but also predictable. It means that we can't guarentee
         // Liveness in the presence of a sophisticated adversary which controls the scheduler.
         // It is only a temporary measure until we implement an actual common coin.
@@ -1437,7 +3436,10 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         self.compute_payloads_for_consensus(decided_meta_votes)
     }
 
-    // Produce the consensused `ObservationKey`in consensus order.
+    // Produce the consensused `ObservationKey`in consensus order: by the earliest position at which
+    // the payload appears in the interesting content of the decided peers, then by the number of
+    // those peers it appears for. Remaining ties are broken by comparing the observation hashes
+    // (see `ObservationKey::consistent_cmp`), so that every honest node picks the same order.
     fn compute_payloads_for_consensus<I>(&self, decided_meta_votes: I) -> Vec<ObservationKey>
     where
         I: IntoIterator<Item = (PeerIndex, bool)>,
@@ -1461,12 +3463,19 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                 map
             });
 
+        let priority = |payload_key: &ObservationKey| {
+            self.observations
+                .get(payload_key)
+                .map_or(0, |info| info.observation.priority())
+        };
+
         payloads
             .into_iter()
             .sorted_by(
                 |(lhs_key, (lhs_count, lhs_min_index)), (rhs_key, (rhs_count, rhs_min_index))| {
-                    lhs_min_index
-                        .cmp(rhs_min_index)
+                    priority(rhs_key)
+                        .cmp(&priority(lhs_key))
+                        .then_with(|| lhs_min_index.cmp(rhs_min_index))
                         .then_with(|| lhs_count.cmp(rhs_count).reverse())
                         .then_with(|| lhs_key.consistent_cmp(rhs_key, &self.peer_list))
                 },
@@ -1476,14 +3485,15 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     }
 
     fn create_blocks(&self, payload_keys: &[ObservationKey]) -> Result<BlockGroup<T, S::PublicId>> {
-        let voters = self.voters();
+        let history_len = self.meta_election.consensus_history_len();
         let blocks: Result<VecDeque<_>> = payload_keys
             .iter()
-            .map(|payload_key| {
+            .enumerate()
+            .map(|(position, payload_key)| {
                 let votes = self
                     .unconsensused_events(Some(payload_key))
                     .map(|event| event.inner())
-                    .filter(|event| voters.contains(event.creator()))
+                    .filter(|event| self.was_voter_when_created(event))
                     .filter_map(|event| {
                         let (vote, key) = event.vote_and_payload_key(&self.observations)?;
                         let creator_id = self.peer_list.get(event.creator()).map(Peer::id)?;
@@ -1492,7 +3502,9 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                     .map(|(_, vote, creator_id)| (creator_id.clone(), vote.clone()))
                     .collect();
 
-                Block::new(&votes)
+                let mut block = Block::new(&votes)?;
+                block.set_index((history_len + position) as u64);
+                Ok(block)
             })
             .filter(|block| match block {
                 Err(Error::MissingVotes) => false,
@@ -1507,6 +3519,43 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Ok(BlockGroup(blocks?))
     }
 
+    // Returns whether the creator of the given event was a voter at the time the event was created.
+    // That's the case if they are still a voter, or if they were a voter of the meta-election which
+    // decided on their removal and the event doesn't descend from votes for that removal by a
+    // supermajority of that meta-election's voters. Only the graph and the consensus history are
+    // consulted, so all peers agree on the outcome regardless of when they reached the consensus.
+    fn was_voter_when_created(&self, event: &Event<S::PublicId>) -> bool {
+        if self.voters().contains(event.creator()) {
+            return true;
+        }
+
+        let (removal_key, removal_voters) =
+            if let Some(removal) = self.meta_election.removal(event.creator()) {
+                removal
+            } else {
+                return false;
+            };
+
+        if !removal_voters.contains(event.creator()) {
+            return false;
+        }
+
+        let num_removal_votes_seen = removal_voters
+            .iter()
+            .filter(|peer_index| {
+                self.peer_list
+                    .get(*peer_index)
+                    .into_iter()
+                    .flat_map(|peer| peer.events())
+                    .filter_map(|event_index| self.graph.get(event_index))
+                    .filter(|vote| vote.payload_key() == Some(removal_key))
+                    .any(|vote| event.is_descendant_of(vote))
+            })
+            .count();
+
+        !is_more_than_two_thirds(num_removal_votes_seen, removal_voters.len())
+    }
+
     // Returns the number of peers that created events which are seen by event X (descendant) and
     // see event Y (ancestor). This means number of peers through which there is a directed path
     // between x and y, excluding peers contains fork.
@@ -1517,14 +3566,17 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     ) -> usize {
         x.last_ancestors()
             .filter(|(peer_index, event_index)| {
-                for event_idx in self.peer_list.events_by_index(*peer_index, *event_index) {
-                    if let Ok(event) = self.get_known_event(event_idx) {
-                        if x.sees(event) && event.sees(y) {
-                            return true;
-                        }
-                    }
+                // `x` sees none of the events by a peer whose fork it is aware of, so there's no
+                // need to look at them individually. Otherwise `x.sees(event)` reduces to
+                // `x.is_descendant_of(event)`.
+                if x.descends_from_fork(*peer_index) {
+                    return false;
                 }
-                false
+
+                self.peer_list
+                    .events_by_index(*peer_index, *event_index)
+                    .filter_map(|event_idx| self.get_known_event(event_idx).ok())
+                    .any(|event| x.is_descendant_of(event) && event.sees(y))
             })
             .count()
     }
@@ -1542,6 +3594,22 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         )
     }
 
+    // Constructs a `Requesting` event naming `peer_id` as the recipient of our next `Request`, then
+    // adds it to our graph.
+    fn create_requesting_event(&mut self, peer_id: &S::PublicId) -> Result<()> {
+        let self_parent = self.peer_list.last_event(PeerIndex::OUR).ok_or_else(|| {
+            log_or_panic_if!(
+                self.panic_on_inconsistency,
+                "{:?} missing our own last event hash.",
+                self.our_pub_id()
+            );
+            Error::Logic
+        })?;
+        let sync_event = Event::new_from_requesting(self_parent, peer_id, self.event_context())?;
+        let _ = self.add_event(sync_event)?;
+        Ok(())
+    }
+
     // Constructs a sync event to prove receipt of a `Request` or `Response` (depending on the value
     // of `is_request`) from `src`, then add it to our graph.
     fn create_sync_event(&mut self, is_request: bool, other_parent: EventIndex) -> Result<()> {
@@ -1598,11 +3666,19 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     // Returns an iterator over `self.events` which will yield all the events we think `peer_id`
     // doesn't yet know about.  We should already have checked that we know `peer_id` and that we
     // have recorded at least one event from this peer before calling this function.
-    fn events_to_gossip_to_peer(&self, peer_index: PeerIndex) -> Result<Vec<&Event<S::PublicId>>> {
+    fn events_to_gossip_to_peer(
+        &self,
+        peer_index: PeerIndex,
+    ) -> Result<impl Iterator<Item = &Event<S::PublicId>>> {
         let last_event = if let Some(event_index) = self.peer_list.last_event(peer_index) {
             self.get_known_event(event_index)?
         } else {
-            log_or_panic!("{:?} doesn't have peer {:?}", self.our_pub_id(), peer_index);
+            log_or_panic_if!(
+                self.panic_on_inconsistency,
+                "{:?} doesn't have peer {:?}",
+                self.our_pub_id(),
+                peer_index
+            );
             return Err(Error::Logic);
         };
 
@@ -1618,9 +3694,8 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Ok(self
             .graph
             .iter()
-            .filter(|event| inclusion_list[event.topological_index()])
-            .map(|event| event.inner())
-            .collect())
+            .filter(move |event| inclusion_list[event.topological_index()])
+            .map(|event| event.inner()))
     }
 
     fn flush_pending_events(&mut self) -> Result<()> {
@@ -1641,6 +3716,11 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         &mut self,
         event: PendingEvent<T, S::PublicId>,
     ) -> Result<()> {
+        // Observers never create events of their own, so there's no point in keeping them.
+        if self.observer_only {
+            return Ok(());
+        }
+
         // Store as pending events if we do not have the initial event, which means we are
         // not voter yet.
         if self.peer_list.last_event(PeerIndex::OUR).is_none() {
@@ -1677,7 +3757,6 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         }
     }
 
-    #[cfg(any(all(test, feature = "mock"), feature = "malice-detection"))]
     fn event_payload<'a>(
         &'a self,
         event: &Event<S::PublicId>,
@@ -1694,7 +3773,8 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .get(event.creator())
             .map(Peer::id)
             .ok_or_else(|| {
-                log_or_panic!(
+                log_or_panic_if!(
+                    self.panic_on_inconsistency,
                     "{:?} doesn't know the creator of {:?}",
                     self.our_pub_id(),
                     event
@@ -1777,12 +3857,21 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         self.detect_other_parent_by_same_creator(event)?;
         self.detect_self_parent_by_different_creator(event)?;
         self.detect_invalid_sync_event(event)?;
+        self.detect_invalid_payload(event)?;
+        self.detect_oversized_payload(event)?;
 
         self.detect_unexpected_genesis(event);
         self.detect_missing_genesis(event);
         self.detect_duplicate_vote(event);
         self.detect_fork(event);
+        self.detect_forked_requesting(event);
         self.detect_invalid_accusations(event);
+        self.detect_forged_accusation(event);
+
+        // NOTE: there is no detection of peers relaying events created by peers they don't know
+        // about, because we don't track the membership list of each peer. We do reject events whose
+        // creator is unknown to us when unpacking them (`Error::UnknownPeer`), so such relaying
+        // can't get events from strangers into our graph.
 
         Ok(())
     }
@@ -1804,6 +3893,39 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         Err(Error::InvalidEvent)
     }
 
+    // Detect if the event carries an opaque payload which fails validation.
+    fn detect_invalid_payload(&mut self, event: &Event<S::PublicId>) -> Result<()> {
+        let is_valid = self
+            .event_payload(event)
+            .map(|observation| observation.opaque_payloads().iter().all(T::validate))
+            .unwrap_or(true);
+        if is_valid {
+            return Ok(());
+        }
+
+        // Return an error to prevent the invalid payload from being consensused.
+        let packed_event = Box::new(event.pack(self.event_context())?);
+        self.accuse(event.creator(), Malice::InvalidPayload(packed_event));
+        Err(Error::InvalidEvent)
+    }
+
+    // Detect if the event carries an observation larger than we accept.
+    fn detect_oversized_payload(&mut self, event: &Event<S::PublicId>) -> Result<()> {
+        let is_oversized = self
+            .event_payload(event)
+            .map(|observation| observation.serialised_size() > self.max_observation_size)
+            .unwrap_or(false);
+        if !is_oversized {
+            return Ok(());
+        }
+
+        // Return an error to prevent the payload from bloating our graph any further. As the event
+        // doesn't make it into any honest graph, the accusation can't point at it and so can only
+        // be decided on by consensus.
+        self.accuse(event.creator(), Malice::Unprovable(UnprovableMalice::Spam));
+        Err(Error::InvalidEvent)
+    }
+
     // Detect if the event's other_parent has the same creator as this event.
     fn detect_other_parent_by_same_creator(&mut self, event: &Event<S::PublicId>) -> Result<()> {
         if let Some(other_parent) = self.graph.other_parent(event) {
@@ -1974,6 +4096,61 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         }
     }
 
+    // Detect whether the event is a `Requesting` event forking from other `Requesting` events by
+    // the same creator which name a different recipient. One accusation is raised per such pair,
+    // with the hashes in ascending order, so that every peer raises the same set of accusations
+    // regardless of the order in which it receives the forked events.
+    // Raises a single accusation per set of `Requesting` events forked from the same self-parent:
+    // only for the first one naming a different recipient than the others seen so far. Accusing
+    // every conflicting pair would make `n` forked events produce `O(n^2)` accusations.
+    fn detect_forked_requesting(&mut self, event: &Event<S::PublicId>) {
+        let recipient = if let Some(recipient) = event.requesting_recipient() {
+            recipient
+        } else {
+            return;
+        };
+
+        let others = self
+            .peer_list
+            .events_by_index(event.creator(), event.index_by_creator())
+            .filter_map(|index| self.graph.get(index))
+            .filter(|other_event| {
+                other_event.hash() != event.hash()
+                    && other_event.self_parent() == event.self_parent()
+            })
+            .filter_map(|other_event| {
+                other_event
+                    .requesting_recipient()
+                    .map(|other_recipient| (*other_event.hash(), other_recipient))
+            })
+            .collect_vec();
+
+        // If the earlier events already name different recipients, they've been accused already.
+        if !others
+            .iter()
+            .map(|(_, other_recipient)| other_recipient)
+            .all_equal()
+        {
+            return;
+        }
+
+        let other_hash = if let Some((other_hash, _)) = others
+            .iter()
+            .find(|(_, other_recipient)| *other_recipient != recipient)
+        {
+            *other_hash
+        } else {
+            return;
+        };
+
+        let malice = if other_hash < *event.hash() {
+            Malice::ForkedRequesting(other_hash, *event.hash())
+        } else {
+            Malice::ForkedRequesting(*event.hash(), other_hash)
+        };
+        self.accuse(event.creator(), malice);
+    }
+
     fn is_first_fork(&self, event: &Event<S::PublicId>) -> bool {
         let same_index_events = self
             .peer_list
@@ -2003,7 +4180,12 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
                     ref offender,
                     ref malice,
                 }) => {
-                    if malice.is_provable() && !self.we_have_accused(offender, malice) {
+                    // Accusations embedding a forged event are handled by
+                    // `detect_forged_accusation` when the accusation itself is received.
+                    if malice.is_provable()
+                        && !Self::embeds_forged_event(malice)
+                        && !self.we_have_accused(offender, malice)
+                    {
                         invalid_accusations.push(*self_parent.hash());
                     }
                     self_parent_index = self_parent.self_parent();
@@ -2019,6 +4201,25 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         }
     }
 
+    // Detect if the event carries an accusation whose embedded event doesn't carry a valid
+    // signature of its supposed creator, i.e. the accuser forged the evidence.
+    fn detect_forged_accusation(&mut self, event: &Event<S::PublicId>) {
+        let forged = match self.event_payload(event) {
+            Some(Observation::Accusation { malice, .. }) => Self::embeds_forged_event(malice),
+            _ => false,
+        };
+        if forged {
+            self.accuse(event.creator(), Malice::InvalidAccusation(*event.hash()));
+        }
+    }
+
+    fn embeds_forged_event(malice: &Malice<T, S::PublicId>) -> bool {
+        malice
+            .embedded_packed_event()
+            .map(|packed_event| !packed_event.has_valid_signature())
+            .unwrap_or(false)
+    }
+
     fn we_have_accused(&self, offender: &S::PublicId, malice: &Malice<T, S::PublicId>) -> bool {
         let their_accusation = if let Some(offender_index) = self.peer_list.get_index(offender) {
             (offender_index, malice)
@@ -2035,6 +4236,15 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             return true;
         }
 
+        // ...or in those waiting for approval...
+        if self
+            .unapproved_accusations
+            .iter()
+            .any(|(our_offender, our_malice)| our_offender == offender && our_malice == malice)
+        {
+            return true;
+        }
+
         // ...then in our events...
         self.peer_list
             .our_events()
@@ -2059,13 +4269,133 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .any(|our_accusation| their_accusation == our_accusation)
     }
 
+    // Detect whether the message consists solely of events topologically older than the last event
+    // the sender already gossiped to us. Such messages carry no information for us, so if the
+    // sender keeps sending them, accuse it of spamming. A message we already synced with is a
+    // redelivery by the transport, which we ignore as benign (see `handle_request`), so it counts
+    // neither way.
+    fn detect_stale_gossip(
+        &mut self,
+        src_index: PeerIndex,
+        packed_events: &[PackedEvent<T, S::PublicId>],
+    ) {
+        let is_redelivery = packed_events
+            .last()
+            .and_then(|packed_event| self.graph.get_index(&packed_event.compute_hash()))
+            .map_or(false, |event_index| self.have_synced_with(event_index));
+        if is_redelivery {
+            return;
+        }
+
+        let watermark = if let Some(event_index) = self.peer_list.last_gossiped_event_by(src_index)
+        {
+            event_index
+        } else {
+            return;
+        };
+
+        let stale = !packed_events.is_empty()
+            && packed_events.iter().all(|packed_event| {
+                self.graph
+                    .get_index(&packed_event.compute_hash())
+                    .map(|event_index| event_index < watermark)
+                    .unwrap_or(false)
+            });
+
+        if self.peer_list.record_stale_gossip_by(src_index, stale) == STALE_GOSSIP_LIMIT {
+            debug!(
+                "{:?} received {} consecutive stale gossip messages from {:?}",
+                self.our_pub_id(),
+                STALE_GOSSIP_LIMIT,
+                src_index
+            );
+            self.accuse(src_index, Malice::Unprovable(UnprovableMalice::Spam));
+        }
+    }
+
     fn detect_premature_gossip(&self) -> Result<()> {
         self.confirm_self_state(PeerState::DKG)
             .map_err(|_| Error::PrematureGossip)
     }
 
     fn accuse(&mut self, offender: PeerIndex, malice: Malice<T, S::PublicId>) {
-        self.pending_accusations.push((offender, malice));
+        if !self.manual_accusation_approval {
+            self.pending_accusations.push((offender, malice));
+            return;
+        }
+
+        let offender = match self.peer_list.get(offender) {
+            Some(peer) => peer.id().clone(),
+            None => return,
+        };
+        let accusation = (offender, malice);
+        if self.unapproved_accusations.contains(&accusation)
+            || self.dismissed_accusations.contains(&accusation)
+        {
+            return;
+        }
+        if self.unapproved_accusations.len() >= self.max_pending_accusations {
+            debug!(
+                "{:?} has too many accusations waiting for approval, dropping {:?}",
+                self.our_pub_id(),
+                accusation
+            );
+            return;
+        }
+
+        self.unapproved_accusations.push(accusation);
+    }
+
+    // Returns the number of accusations which became pending since there were
+    // `num_known_accusations` of them, i.e. those raised as a result of the message being handled.
+    fn num_accusations_since(&self, num_known_accusations: usize) -> usize {
+        self.pending_accusations
+            .len()
+            .saturating_sub(num_known_accusations)
+    }
+
+    // Drops the pending accusations over the limit and returns an error if there were any. They
+    // must not be kept, as the error prevents them from being raised, and so they would make us
+    // reject every further message.
+    fn confirm_pending_accusations_within_limit(&mut self) -> Result<()> {
+        if self.pending_accusations.len() > self.max_pending_accusations {
+            debug!(
+                "{:?} has too many pending accusations ({})",
+                self.our_pub_id(),
+                self.pending_accusations.len()
+            );
+            self.pending_accusations
+                .truncate(self.max_pending_accusations);
+            Err(Error::TooManyAccusations)
+        } else {
+            Ok(())
+        }
+    }
+
+    // If the given event carries an accusation, record it in `accusations_by_creator`.
+    fn record_accusation(&mut self, event_index: EventIndex) {
+        let (creator, offender, malice) = match self.graph.get(event_index) {
+            Some(event) => match self.event_payload(event.inner()) {
+                Some(Observation::Accusation { offender, malice }) => {
+                    (event.creator(), offender.clone(), malice.clone())
+                }
+                _ => return,
+            },
+            None => return,
+        };
+
+        let accusations = self
+            .accusations_by_creator
+            .entry(creator)
+            .or_insert_with(Vec::new);
+        // Events are inserted in topological order, so this keeps the list sorted. It also skips
+        // an event which has already been recorded.
+        if accusations
+            .last()
+            .map_or(true, |(last_index, ..)| *last_index < event_index)
+        {
+            accusations.push((event_index, offender, malice));
+        }
     }
 
     fn accusations_by_peer_since(
@@ -2073,14 +4403,21 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
         peer_index: PeerIndex,
         oldest_event: Option<EventIndex>,
     ) -> impl Iterator<Item = (PeerIndex, &Malice<T, S::PublicId>)> {
-        self.graph
-            .iter_from(oldest_event.map(EventIndex::topological_index).unwrap_or(0))
-            .filter(move |event| event.creator() == peer_index)
-            .filter_map(move |event| match self.event_payload(event.inner()) {
-                Some(Observation::Accusation { offender, malice }) => Some((offender, malice)),
-                _ => None,
-            })
-            .filter_map(move |(offender, malice)| {
+        let accusations = self
+            .accusations_by_creator
+            .get(&peer_index)
+            .map(|accusations| &accusations[..])
+            .unwrap_or(&[]);
+        let oldest_index = oldest_event.map(EventIndex::topological_index).unwrap_or(0);
+        let start = match accusations.binary_search_by_key(&oldest_index, |(event_index, ..)| {
+            event_index.topological_index()
+        }) {
+            Ok(start) | Err(start) => start,
+        };
+
+        accusations[start..]
+            .iter()
+            .filter_map(move |(_, offender, malice)| {
                 self.peer_list
                     .get_index(offender)
                     .map(|offender| (offender, malice))
@@ -2180,7 +4517,7 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
             .max_by_key(|event_index| event_index.topological_index());
         if let Some(index) = last_malice_event_accused_by_peer {
             self.peer_list
-                .update_accomplice_event_checkpoint_by(creator, index);
+                .update_accomplice_event_checkpoint_by(creator, index)?;
         }
 
         Ok(())
@@ -2248,18 +4585,25 @@ impl<T: NetworkEvent, S: SecretId> Drop for Parsec<T, S> {
             observations: &self.observations,
             secure_rng: &self.secure_rng,
             key_gens_and_next_id: (&self.key_gen, &self.key_gen_next_id),
+            dump_dir: self.dump_dir.as_ref().map(PathBuf::as_path),
             info: &dump_graph::DumpGraphContext::DroppingParsec,
         });
     }
 }
 
 fn get_known_event<'a, P: PublicId>(
+    panic_on_inconsistency: bool,
     our_pub_id: &P,
     graph: &'a Graph<P>,
     event_index: EventIndex,
 ) -> Result<IndexedEventRef<'a, P>> {
     graph.get(event_index).ok_or_else(|| {
-        log_or_panic!("{:?} doesn't have event {:?}", our_pub_id, event_index);
+        log_or_panic_if!(
+            panic_on_inconsistency,
+            "{:?} doesn't have event {:?}",
+            our_pub_id,
+            event_index
+        );
         Error::Logic
     })
 }
@@ -2275,6 +4619,11 @@ enum PostProcessAction {
 #[cfg(feature = "malice-detection")]
 type Accusations<T, P> = Vec<(PeerIndex, Malice<T, P>)>;
 
+// Accusation events by creator: (accusing event, offender, malice), ordered by the event index.
+#[cfg(feature = "malice-detection")]
+type AccusationsByCreator<T, P> = BTreeMap<PeerIndex, Vec<(EventIndex, P, Malice<T, P>)>>;
+
+#[derive(Clone)]
 enum PendingEvent<T: NetworkEvent, P: PublicId> {
     Sync {
         is_request: bool,
@@ -2304,6 +4653,39 @@ impl<T: NetworkEvent, S: SecretId> Parsec<T, S> {
     pub(crate) fn ignore_process_events(&self) -> bool {
         self.ignore_process_events
     }
+
+    /// Sets the oracle consulted before every coin toss. It's called with the round of the
+    /// meta-vote and the ID of the peer the meta-vote is for. If it returns `Some(coin)`, that is
+    /// used as the result of the coin toss, otherwise the coin is tossed as usual.
+    ///
+    /// This allows tests to force specific round outcomes deterministically.
+    pub fn set_coin_oracle<F>(&mut self, oracle: F)
+    where
+        F: Fn(usize, &S::PublicId) -> Option<bool> + 'static,
+    {
+        self.coin_oracle = Some(Box::new(oracle));
+    }
+
+    /// Like `vote_for`, but also records `logical_time` against the event carrying the vote, e.g.
+    /// the simulated time of a test harness. The timestamp is opaque to `Parsec` and never affects
+    /// consensus. It isn't sent to other peers either, so it's only known to us and can be
+    /// retrieved via `logical_time`.
+    pub fn vote_for_at(
+        &mut self,
+        observation: Observation<T, S::PublicId>,
+        logical_time: u64,
+    ) -> Result<()> {
+        self.vote_for(observation)?;
+        let event_hash = *self.get_known_event(self.our_last_event_index()?)?.hash();
+        let _ = self.logical_times.insert(event_hash, logical_time);
+        Ok(())
+    }
+
+    /// Returns the logical timestamp recorded via `vote_for_at` for the event with the given hash,
+    /// or `None` if there is none.
+    pub fn logical_time(&self, event_hash: &EventHash) -> Option<u64> {
+        self.logical_times.get(event_hash).cloned()
+    }
 }
 
 #[cfg(any(feature = "testing", all(test, feature = "mock")))]
@@ -2358,6 +4740,15 @@ impl Parsec<Transaction, PeerId> {
         parsec.meta_election = parsed_contents.meta_election;
         parsec.peer_list = parsed_contents.peer_list;
         parsec.observations = parsed_contents.observations;
+
+        #[cfg(feature = "malice-detection")]
+        {
+            let event_indices: Vec<_> = parsec.graph.indices_from(0).collect();
+            for event_index in event_indices {
+                parsec.record_accusation(event_index);
+            }
+        }
+
         parsec
     }
 
@@ -2428,7 +4819,9 @@ impl<T: NetworkEvent, S: SecretId> TestParsec<T, S> {
         &self,
         peer_index: PeerIndex,
     ) -> Result<Vec<&Event<S::PublicId>>> {
-        self.0.events_to_gossip_to_peer(peer_index)
+        self.0
+            .events_to_gossip_to_peer(peer_index)
+            .map(Iterator::collect)
     }
 
     pub fn get_peer_index(&self, peer_id: &S::PublicId) -> Result<PeerIndex> {
@@ -2467,7 +4860,17 @@ impl TestParsec<Transaction, PeerId> {
 
     pub fn change_peer_state(&mut self, peer_id: &PeerId, state: PeerState) {
         let peer_index = unwrap!(self.0.peer_list.get_index(peer_id));
-        self.0.peer_list.change_peer_state(peer_index, state)
+        unwrap!(self.0.peer_list.change_peer_state(peer_index, state))
+    }
+
+    // Removes the peer as if we reached consensus on its removal at the given event.
+    pub fn remove_peer(&mut self, peer_id: &PeerId, deciding_event_index: EventIndex) {
+        let peer_index = unwrap!(self.0.peer_list.get_index(peer_id));
+        unwrap!(self
+            .0
+            .peer_list
+            .remove_peer(peer_index, deciding_event_index));
+        let _ = self.0.meta_election.voters.remove(peer_index);
     }
 
     pub fn pack_event(&self, event: &Event<PeerId>) -> PackedEvent<Transaction, PeerId> {
@@ -2514,6 +4917,12 @@ impl TestParsec<Transaction, PeerId> {
             unwrap!(self.peer_list.remove_last_event(event.creator()))
         );
 
+        if let Some(accusations) = self.0.accusations_by_creator.get_mut(&event.creator()) {
+            if accusations.last().map(|(index, ..)| *index) == Some(event_index) {
+                let _ = accusations.pop();
+            }
+        }
+
         if let Some(payload_key) = event.payload_key() {
             let _ = self
                 .0
@@ -2581,7 +4990,7 @@ impl TestParsec<Transaction, PeerId> {
     }
 
     pub fn add_peer(&mut self, peer_id: PeerId, state: PeerState) {
-        let _ = self.0.peer_list.add_peer(peer_id, state);
+        let _ = unwrap!(self.0.peer_list.add_peer(peer_id, state));
     }
 
     pub fn restart_consensus(&mut self) -> Result<()> {
@@ -2612,6 +5021,100 @@ impl<T: NetworkEvent, S: SecretId> DerefMut for TestParsec<T, S> {
     }
 }
 
+#[cfg(any(test, feature = "testing"))]
+impl<T: NetworkEvent, S: SecretId> Borrow<Parsec<T, S>> for TestParsec<T, S> {
+    fn borrow(&self) -> &Parsec<T, S> {
+        &self.0
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl<T: NetworkEvent, S: SecretId> BorrowMut<Parsec<T, S>> for TestParsec<T, S> {
+    fn borrow_mut(&mut self) -> &mut Parsec<T, S> {
+        &mut self.0
+    }
+}
+
+/// Makes each of the given instances gossip with each of its `gossip_recipients` which is among
+/// them, round after round, until a round leaves them all with no payload waiting for consensus and
+/// without any new consensused payload. Returns the number of rounds run, or `Error::NotStable` if
+/// that doesn't happen within `max_rounds`. Any error while gossiping is returned as is.
+#[cfg(any(test, feature = "testing"))]
+pub fn gossip_until_stable<T, S, P>(parsecs: &mut [P], max_rounds: usize) -> Result<usize>
+where
+    T: NetworkEvent,
+    S: SecretId,
+    P: BorrowMut<Parsec<T, S>>,
+{
+    let num_consensused = |parsecs: &[P]| -> Vec<usize> {
+        parsecs
+            .iter()
+            .map(|parsec| parsec.borrow().meta_election.consensus_history_len())
+            .collect()
+    };
+
+    for round in 1..=max_rounds {
+        let num_consensused_before = num_consensused(parsecs);
+        gossip_round(parsecs)?;
+
+        let is_stable = num_consensused(parsecs) == num_consensused_before
+            && parsecs.iter().all(|parsec| {
+                parsec
+                    .borrow()
+                    .meta_election
+                    .unconsensused_events(None)
+                    .next()
+                    .is_none()
+            });
+        if is_stable {
+            return Ok(round);
+        }
+    }
+
+    Err(Error::NotStable)
+}
+
+/// Makes each of the given instances gossip once with each of its `gossip_recipients` which is
+/// among them. Any error while gossiping is returned as is.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn gossip_round<T, S, P>(parsecs: &mut [P]) -> Result<()>
+where
+    T: NetworkEvent,
+    S: SecretId,
+    P: BorrowMut<Parsec<T, S>>,
+{
+    for src_index in 0..parsecs.len() {
+        let src_id = parsecs[src_index].borrow().our_pub_id().clone();
+        let recipients: Vec<_> = parsecs[src_index]
+            .borrow()
+            .gossip_recipients()
+            .cloned()
+            .collect();
+        for dst_id in recipients {
+            let dst_index = if let Some(index) = parsecs
+                .iter()
+                .position(|parsec| *parsec.borrow().our_pub_id() == dst_id)
+            {
+                index
+            } else {
+                continue;
+            };
+
+            let request = parsecs[src_index].borrow_mut().create_gossip(&dst_id)?;
+            let response = parsecs[dst_index]
+                .borrow_mut()
+                .handle_request(&src_id, request)?;
+            if !response.is_empty() {
+                parsecs[src_index]
+                    .borrow_mut()
+                    .handle_response(&dst_id, response)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Get the parsec graph snapshot with inserted events out of order.
 #[cfg(all(test, any(feature = "testing", feature = "mock")))]
 pub(crate) fn get_graph_snapshot<T: NetworkEvent, S: SecretId>(
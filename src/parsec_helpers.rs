@@ -6,13 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{gossip::AbstractEventRef, observation::ObservationKey};
+use crate::{gossip::AbstractEventRef, observation::ObservationKey, peer_list::PeerIndex};
 use itertools::Itertools;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::usize;
 
 /// Find interesting payloads for the builder_event.
-/// For payload observed from builder_event, order them by creation index.
+/// For payload observed from builder_event, order them by priority (highest first), then by
+/// creation index.
 pub(crate) fn find_interesting_content_for_event<'a, E>(
     builder_event: E,
     unconsensused_events: impl Iterator<Item = E>,
@@ -20,6 +22,7 @@ pub(crate) fn find_interesting_content_for_event<'a, E>(
     is_descendant: impl Fn(E, E) -> bool,
     is_already_interesting_content: impl Fn(&ObservationKey) -> bool,
     is_interesting_payload: impl Fn(&ObservationKey) -> bool,
+    priority: impl Fn(&ObservationKey) -> u8,
 ) -> Vec<ObservationKey>
 where
     E: AbstractEventRef<'a>,
@@ -69,6 +72,7 @@ where
         })
         .map(|(event, payload_key)| {
             (
+                priority(payload_key),
                 if has_builder_creator(*event) {
                     event.index_by_creator()
                 } else {
@@ -79,21 +83,47 @@ where
         })
         .collect_vec();
 
-    // Sort the payloads in the order the creator voted for them, followed by the ones
-    // not voted for by the creator (if any).
-    interesting_payload_keys.sort_by(|(l_index, l_key), (r_index, r_key)| {
-        l_index
-            .cmp(r_index)
-            .then_with(|| consistent_cmp(l_key, r_key))
-    });
+    // Sort the payloads by priority, highest first. Within the same priority, sort them in the
+    // order the creator voted for them, followed by the ones not voted for by the creator (if
+    // any).
+    interesting_payload_keys.sort_by(
+        |(l_priority, l_index, l_key), (r_priority, r_index, r_key)| {
+            r_priority
+                .cmp(l_priority)
+                .then_with(|| l_index.cmp(r_index))
+                .then_with(|| consistent_cmp(l_key, r_key))
+        },
+    );
 
     interesting_payload_keys
         .into_iter()
-        .map(|(_, key)| key)
+        .map(|(_, _, key)| key)
         .cloned()
         .collect()
 }
 
+/// Moves the payloads exceeding their proposer's quota to the end, keeping the relative order of
+/// the payloads otherwise. The first `peer_quota` payloads of each proposer stay in place. Payloads
+/// for which `proposer` returns `None` are never moved nor counted.
+pub(crate) fn defer_payloads_over_quota(
+    payload_keys: Vec<ObservationKey>,
+    proposer: impl Fn(&ObservationKey) -> Option<PeerIndex>,
+    peer_quota: usize,
+) -> Vec<ObservationKey> {
+    let mut counts = BTreeMap::new();
+    let (mut within_quota, over_quota): (Vec<_>, Vec<_>) =
+        payload_keys.into_iter().partition(|payload_key| {
+            proposer(payload_key).map_or(true, |peer_index| {
+                let count = counts.entry(peer_index).or_insert(0);
+                *count += 1;
+                *count <= peer_quota
+            })
+        });
+
+    within_quota.extend(over_quota);
+    within_quota
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +238,8 @@ mod tests {
         struct PayloadProperties {
             is_already_interesting_content: bool,
             is_interesting_payload: bool,
+            /// Index into `OPAQUE_HASHES` of the payload with a higher priority than the others.
+            high_priority_payload: Option<usize>,
         }
 
         struct Events {
@@ -274,6 +306,10 @@ mod tests {
                 |event_x, _event_y| event_x.has_ancestors,
                 |_payload_key| payload_properties.is_already_interesting_content,
                 |_payload_key| payload_properties.is_interesting_payload,
+                |payload_key| match payload_properties.high_priority_payload {
+                    Some(index) if *payload_key.hash() == OPAQUE_HASHES[index] => 1,
+                    _ => 0,
+                },
             );
 
             assert_eq!(
@@ -294,6 +330,7 @@ mod tests {
                 payload_properties: PayloadProperties {
                     is_already_interesting_content: false,
                     is_interesting_payload: true,
+                    high_priority_payload: None,
                 },
                 expected_payloads: vec![Supermajority(1), Supermajority(2)],
                 is_reverse: false,
@@ -309,6 +346,7 @@ mod tests {
                 payload_properties: PayloadProperties {
                     is_already_interesting_content: false,
                     is_interesting_payload: true,
+                    high_priority_payload: None,
                 },
                 expected_payloads: vec![
                     Single(1, PEER_IDS[6]),
@@ -320,6 +358,22 @@ mod tests {
             });
         }
 
+        #[test]
+        /// Higher priority payloads come first, regardless of the vote order
+        fn high_priority_payload_first() {
+            test_find_interesting_content_for_event(TestSimpleData {
+                events: Events::new_basic_setup(ConsensusMode::Supermajority)
+                    .with_builder_event_sees_other(),
+                payload_properties: PayloadProperties {
+                    is_already_interesting_content: false,
+                    is_interesting_payload: true,
+                    high_priority_payload: Some(2),
+                },
+                expected_payloads: vec![Supermajority(2), Supermajority(1)],
+                is_reverse: false,
+            });
+        }
+
         #[test]
         /// Filter out already interesting payloads
         fn all_payloads_already_interesting() {
@@ -329,6 +383,7 @@ mod tests {
                 payload_properties: PayloadProperties {
                     is_already_interesting_content: true,
                     is_interesting_payload: true,
+                    high_priority_payload: None,
                 },
                 expected_payloads: vec![],
                 is_reverse: false,
@@ -344,10 +399,41 @@ mod tests {
                 payload_properties: PayloadProperties {
                     is_already_interesting_content: false,
                     is_interesting_payload: false,
+                    high_priority_payload: None,
                 },
                 expected_payloads: vec![],
                 is_reverse: false,
             });
         }
     }
+
+    #[test]
+    fn defer_payloads_over_quota() {
+        let key = |index: usize| ObservationKey::Supermajority(OPAQUE_HASHES[index]);
+        // Payloads 0 to 3 are proposed by peer 0, 4 and 5 by peer 1, 6 by nobody.
+        let proposer = |payload_key: &ObservationKey| {
+            let index = unwrap!(OPAQUE_HASHES
+                .iter()
+                .position(|hash| hash == payload_key.hash()));
+            match index {
+                0..=3 => Some(PEER_IDS[0]),
+                4 | 5 => Some(PEER_IDS[1]),
+                _ => None,
+            }
+        };
+
+        let payload_keys = vec![key(0), key(1), key(4), key(2), key(6), key(3), key(5)];
+        assert_eq!(
+            super::defer_payloads_over_quota(payload_keys.clone(), proposer, 1),
+            vec![key(0), key(4), key(6), key(1), key(2), key(3), key(5)]
+        );
+        assert_eq!(
+            super::defer_payloads_over_quota(payload_keys.clone(), proposer, 2),
+            vec![key(0), key(1), key(4), key(6), key(5), key(2), key(3)]
+        );
+        assert_eq!(
+            super::defer_payloads_over_quota(payload_keys.clone(), proposer, 4),
+            payload_keys
+        );
+    }
 }
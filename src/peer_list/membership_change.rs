@@ -0,0 +1,49 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::id::PublicId;
+
+/// A consensused change of the section membership.
+///
+/// `block_index` is the position of the corresponding payload in the sequence of all payloads
+/// consensused so far, starting at 0 for the genesis.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MembershipChange<P: PublicId> {
+    /// The peer was added to the section.
+    Add {
+        /// Public id of the added peer.
+        peer_id: P,
+        /// Index of the block at which the peer was added.
+        block_index: usize,
+    },
+    /// The peer was removed from the section.
+    Remove {
+        /// Public id of the removed peer.
+        peer_id: P,
+        /// Index of the block at which the peer was removed.
+        block_index: usize,
+    },
+}
+
+impl<P: PublicId> MembershipChange<P> {
+    /// Returns the public id of the added or removed peer.
+    pub fn peer_id(&self) -> &P {
+        match *self {
+            MembershipChange::Add { ref peer_id, .. }
+            | MembershipChange::Remove { ref peer_id, .. } => peer_id,
+        }
+    }
+
+    /// Returns the index of the block at which the change happened.
+    pub fn block_index(&self) -> usize {
+        match *self {
+            MembershipChange::Add { block_index, .. }
+            | MembershipChange::Remove { block_index, .. } => block_index,
+        }
+    }
+}
@@ -6,11 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod membership_change;
 mod peer;
 mod peer_index;
 mod peer_state;
+mod responsiveness;
 
-pub use self::peer_state::PeerState;
+pub use self::{
+    membership_change::MembershipChange, peer_state::PeerState, responsiveness::ResponsivenessStats,
+};
 #[cfg(all(test, feature = "mock"))]
 pub(crate) use self::snapshot::PeerListSnapshot;
 pub(crate) use self::{
@@ -33,7 +37,11 @@ use std::{
     iter,
 };
 
-pub(crate) struct PeerList<S: SecretId> {
+/// The peers known to a `Parsec` instance, us included, along with their states.
+///
+/// Normally it's built by `Parsec` itself, but it can also be built externally and passed to
+/// [Parsec::from_peer_list](struct.Parsec.html#method.from_peer_list).
+pub struct PeerList<S: SecretId> {
     our_id: S,
     our_peer: Peer<S::PublicId>,
     peers: Vec<Peer<S::PublicId>>,
@@ -41,6 +49,7 @@ pub(crate) struct PeerList<S: SecretId> {
 }
 
 impl<S: SecretId> PeerList<S> {
+    /// Creates a peer list containing only us, in the `inactive` state.
     pub fn new(our_id: S) -> Self {
         let our_peer = Peer::new(our_id.public_id().clone(), PeerState::inactive());
 
@@ -52,15 +61,26 @@ impl<S: SecretId> PeerList<S> {
         }
     }
 
-    pub fn our_id(&self) -> &S {
+    pub(crate) fn our_id(&self) -> &S {
         &self.our_id
     }
 
+    /// Returns a copy of this peer list with `our_id` as our secret ID.
+    pub(crate) fn clone_with_id(&self, our_id: S) -> Self {
+        PeerList {
+            our_id,
+            our_peer: self.our_peer.clone(),
+            peers: self.peers.clone(),
+            indices: self.indices.clone(),
+        }
+    }
+
+    /// Returns our public ID.
     pub fn our_pub_id(&self) -> &S::PublicId {
         &self.our_id.public_id()
     }
 
-    pub fn get_index(&self, peer_id: &S::PublicId) -> Option<PeerIndex> {
+    pub(crate) fn get_index(&self, peer_id: &S::PublicId) -> Option<PeerIndex> {
         if peer_id == self.our_id.public_id() {
             Some(PeerIndex::OUR)
         } else {
@@ -68,11 +88,12 @@ impl<S: SecretId> PeerList<S> {
         }
     }
 
+    /// Returns whether the peer with the given ID is in the list. We always are.
     pub fn contains(&self, peer_id: &S::PublicId) -> bool {
         peer_id == self.our_id.public_id() || self.indices.contains_key(peer_id)
     }
 
-    pub fn get(&self, index: PeerIndex) -> Option<&Peer<S::PublicId>> {
+    pub(crate) fn get(&self, index: PeerIndex) -> Option<&Peer<S::PublicId>> {
         if index == PeerIndex::OUR {
             Some(&self.our_peer)
         } else {
@@ -80,34 +101,34 @@ impl<S: SecretId> PeerList<S> {
         }
     }
 
-    pub fn get_known(&self, index: PeerIndex) -> Result<&Peer<S::PublicId>, Error> {
+    pub(crate) fn get_known(&self, index: PeerIndex) -> Result<&Peer<S::PublicId>, Error> {
         self.get(index).ok_or_else(|| {
-            log_or_panic!(
+            error!(
                 "{:?} does not have peer with index {:?}",
                 self.our_id.public_id(),
                 index
             );
-            Error::UnknownPeer
+            Error::Logic
         })
     }
 
-    fn get_known_mut(&mut self, index: PeerIndex) -> Option<&mut Peer<S::PublicId>> {
+    fn get_known_mut(&mut self, index: PeerIndex) -> Result<&mut Peer<S::PublicId>, Error> {
         if index == PeerIndex::OUR {
-            Some(&mut self.our_peer)
+            Ok(&mut self.our_peer)
         } else if let Some(peer) = self.peers.get_mut(index.0 - 1) {
-            Some(peer)
+            Ok(peer)
         } else {
-            log_or_panic!(
+            error!(
                 "{:?} does not have peer with index {:?}",
                 self.our_id.public_id(),
                 index
             );
-            None
+            Err(Error::Logic)
         }
     }
 
     /// Returns an iterator of peers.
-    pub fn iter(&self) -> impl Iterator<Item = (PeerIndex, &Peer<S::PublicId>)> {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (PeerIndex, &Peer<S::PublicId>)> {
         iter::once((PeerIndex::OUR, &self.our_peer)).chain(
             self.peers
                 .iter()
@@ -117,12 +138,12 @@ impl<S: SecretId> PeerList<S> {
     }
 
     /// Returns an iterator of peers that can vote.
-    pub fn voters(&self) -> impl Iterator<Item = (PeerIndex, &Peer<S::PublicId>)> {
+    pub(crate) fn voters(&self) -> impl Iterator<Item = (PeerIndex, &Peer<S::PublicId>)> {
         self.iter().filter(|(_, peer)| peer.state().can_vote())
     }
 
     /// Returns an iterator of peers that we can send gossip to.
-    pub fn gossip_recipients<'a>(
+    pub(crate) fn gossip_recipients<'a>(
         &'a self,
     ) -> impl Iterator<Item = (PeerIndex, &Peer<S::PublicId>)> + 'a {
         let iter = if self.our_peer.state().can_send() {
@@ -139,39 +160,54 @@ impl<S: SecretId> PeerList<S> {
     }
 
     /// Return public ids of all peers.
-    pub fn all_ids(&self) -> impl Iterator<Item = (PeerIndex, &S::PublicId)> {
+    pub(crate) fn all_ids(&self) -> impl Iterator<Item = (PeerIndex, &S::PublicId)> {
         self.iter().map(|(index, peer)| (index, peer.id()))
     }
 
-    pub fn peer_state(&self, index: PeerIndex) -> PeerState {
+    pub(crate) fn peer_state(&self, index: PeerIndex) -> PeerState {
         self.get(index)
             .map(Peer::state)
             .unwrap_or_else(PeerState::inactive)
     }
 
+    /// Returns our own state.
     pub fn our_state(&self) -> PeerState {
         self.our_peer.state()
     }
 
-    /// Adds a peer in the given state into the map.
-    pub fn add_peer(&mut self, peer_id: S::PublicId, state: PeerState) -> PeerIndex {
+    /// Inserts the peer in the given state. If the peer is already in the list, which is always
+    /// the case for us, `state` is added to its current state instead.
+    pub fn insert_peer(&mut self, peer_id: S::PublicId, state: PeerState) -> Result<(), Error> {
+        if let Some(index) = self.get_index(&peer_id) {
+            self.change_peer_state(index, state)
+        } else {
+            self.add_peer(peer_id, state).map(|_| ())
+        }
+    }
+
+    /// Adds a peer in the given state into the map. Returns `Error::Logic` if it's already in it.
+    pub(crate) fn add_peer(
+        &mut self,
+        peer_id: S::PublicId,
+        state: PeerState,
+    ) -> Result<PeerIndex, Error> {
         if peer_id == *self.our_id.public_id() {
-            log_or_panic!(
+            error!(
                 "{:?} already has self in the peer list",
                 self.our_id.public_id(),
             );
 
-            return PeerIndex::OUR;
+            return Err(Error::Logic);
         }
 
         match self.indices.entry(peer_id) {
             Entry::Occupied(entry) => {
-                log_or_panic!(
+                error!(
                     "{:?} already has {:?} in the peer list",
                     self.our_id.public_id(),
                     entry.key()
                 );
-                *entry.get()
+                Err(Error::Logic)
             }
             Entry::Vacant(entry) => {
                 let index = PeerIndex(self.peers.len() + 1);
@@ -180,33 +216,40 @@ impl<S: SecretId> PeerList<S> {
                 self.peers.push(peer);
                 let _ = entry.insert(index);
 
-                index
+                Ok(index)
             }
         }
     }
 
     /// Remove peer at `peer_index` after reaching consensus on the removal at the event
     /// at `deciding_event_index`.
-    pub fn remove_peer(&mut self, peer_index: PeerIndex, deciding_event_index: EventIndex) {
-        if let Some(peer) = self.get_known_mut(peer_index) {
-            peer.set_removed(deciding_event_index)
-        }
+    pub(crate) fn remove_peer(
+        &mut self,
+        peer_index: PeerIndex,
+        deciding_event_index: EventIndex,
+    ) -> Result<(), Error> {
+        self.get_known_mut(peer_index)?
+            .set_removed(deciding_event_index);
+        Ok(())
     }
 
-    pub fn change_peer_state(&mut self, index: PeerIndex, state: PeerState) {
-        if let Some(peer) = self.get_known_mut(index) {
-            peer.change_state(state);
-        }
+    pub(crate) fn change_peer_state(
+        &mut self,
+        index: PeerIndex,
+        state: PeerState,
+    ) -> Result<(), Error> {
+        self.get_known_mut(index)?.change_state(state);
+        Ok(())
     }
 
     /// Returns the index of the last event created by this peer. Returns `None` if cannot find.
-    pub fn last_event(&self, peer_index: PeerIndex) -> Option<EventIndex> {
+    pub(crate) fn last_event(&self, peer_index: PeerIndex) -> Option<EventIndex> {
         self.get(peer_index)
             .and_then(|peer| peer.events().rev().next())
     }
 
     /// Returns the indices of the events at the given index-by-creator.
-    pub fn events_by_index<'a>(
+    pub(crate) fn events_by_index<'a>(
         &'a self,
         peer_index: PeerIndex,
         index_by_creator: usize,
@@ -217,60 +260,85 @@ impl<S: SecretId> PeerList<S> {
     }
 
     /// Record that the given peer gossiped to us the given event.
-    pub fn record_gossiped_event_by(&mut self, index: PeerIndex, event_index: EventIndex) {
-        if let Some(peer) = self.get_known_mut(index) {
-            if peer
-                .last_gossiped_event
-                .map(|current| current < event_index)
-                .unwrap_or(true)
-            {
-                peer.last_gossiped_event = Some(event_index)
+    pub(crate) fn record_gossiped_event_by(
+        &mut self,
+        index: PeerIndex,
+        event_index: EventIndex,
+    ) -> Result<(), Error> {
+        let peer = self.get_known_mut(index)?;
+        if peer
+            .last_gossiped_event
+            .map(|current| current < event_index)
+            .unwrap_or(true)
+        {
+            peer.last_gossiped_event = Some(event_index)
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the topologically latest event the given peer gossiped to us.
+    #[cfg(feature = "malice-detection")]
+    pub(crate) fn last_gossiped_event_by(&self, index: PeerIndex) -> Option<EventIndex> {
+        self.get(index).and_then(|peer| peer.last_gossiped_event)
+    }
+
+    /// Record whether the latest message from the given peer was stale, i.e. consisted solely of
+    /// events older than what it had already gossiped to us. Returns the number of consecutive
+    /// stale messages received from the peer so far.
+    #[cfg(feature = "malice-detection")]
+    pub(crate) fn record_stale_gossip_by(&mut self, index: PeerIndex, stale: bool) -> usize {
+        if let Ok(peer) = self.get_known_mut(index) {
+            if stale {
+                peer.consecutive_stale_messages += 1;
+            } else {
+                peer.consecutive_stale_messages = 0;
             }
+            peer.consecutive_stale_messages
+        } else {
+            0
         }
     }
 
     #[cfg(feature = "malice-detection")]
-    pub fn accomplice_event_checkpoint_by(&self, peer_index: PeerIndex) -> Option<EventIndex> {
+    pub(crate) fn accomplice_event_checkpoint_by(
+        &self,
+        peer_index: PeerIndex,
+    ) -> Option<EventIndex> {
         self.get(peer_index)
             .and_then(|peer| peer.accomplice_event_checkpoint)
     }
 
     #[cfg(feature = "malice-detection")]
-    pub fn update_accomplice_event_checkpoint_by(
+    pub(crate) fn update_accomplice_event_checkpoint_by(
         &mut self,
         peer_index: PeerIndex,
         event_index: EventIndex,
-    ) {
-        if let Some(peer) = self.get_known_mut(peer_index) {
-            if peer
-                .accomplice_event_checkpoint
-                .map(|current| current.topological_index() < event_index.topological_index())
-                .unwrap_or(true)
-            {
-                peer.accomplice_event_checkpoint = Some(event_index)
-            }
+    ) -> Result<(), Error> {
+        let peer = self.get_known_mut(peer_index)?;
+        if peer
+            .accomplice_event_checkpoint
+            .map(|current| current.topological_index() < event_index.topological_index())
+            .unwrap_or(true)
+        {
+            peer.accomplice_event_checkpoint = Some(event_index)
         }
+        Ok(())
     }
 
     /// Adds event created by the peer.
-    pub fn add_event(&mut self, event: IndexedEventRef<S::PublicId>) {
-        if let Some(peer) = self.get_known_mut(event.creator()) {
-            peer.add_event(event.index_by_creator(), event.event_index())
-        }
+    pub(crate) fn add_event(&mut self, event: IndexedEventRef<S::PublicId>) -> Result<(), Error> {
+        self.get_known_mut(event.creator())?
+            .add_event(event.index_by_creator(), event.event_index())
     }
 
     /// Removes last event from its creator.
     #[cfg(any(all(test, feature = "mock"), feature = "testing"))]
-    pub fn remove_last_event(&mut self, creator: PeerIndex) -> Option<EventIndex> {
-        if let Some(peer) = self.get_known_mut(creator) {
-            peer.remove_last_event()
-        } else {
-            None
-        }
+    pub(crate) fn remove_last_event(&mut self, creator: PeerIndex) -> Option<EventIndex> {
+        self.get_known_mut(creator).ok()?.remove_last_event()
     }
 
     /// Indices of events of the given creator, in insertion order.
-    pub fn peer_events<'a>(
+    pub(crate) fn peer_events<'a>(
         &'a self,
         peer_index: PeerIndex,
     ) -> impl DoubleEndedIterator<Item = EventIndex> + 'a {
@@ -278,7 +346,7 @@ impl<S: SecretId> PeerList<S> {
     }
 
     /// Indices of our events in insertion order.
-    pub fn our_events<'a>(&'a self) -> impl DoubleEndedIterator<Item = EventIndex> + 'a {
+    pub(crate) fn our_events<'a>(&'a self) -> impl DoubleEndedIterator<Item = EventIndex> + 'a {
         self.peer_events(PeerIndex::OUR)
     }
 }
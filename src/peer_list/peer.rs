@@ -8,6 +8,7 @@
 
 use super::peer_state::PeerState;
 use crate::{
+    error::Error,
     gossip::{EventIndex, IndexedEventRef},
     id::PublicId,
 };
@@ -17,7 +18,7 @@ use std::{
     iter::{self, FromIterator},
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Peer<P: PublicId> {
     id: P,
     presence: Presence,
@@ -27,6 +28,10 @@ pub(crate) struct Peer<P: PublicId> {
     // accusations.
     #[cfg(feature = "malice-detection")]
     pub accomplice_event_checkpoint: Option<EventIndex>,
+    // Number of consecutive messages from this peer consisting solely of events older than the
+    // last one it already gossiped to us.
+    #[cfg(feature = "malice-detection")]
+    pub consecutive_stale_messages: usize,
 }
 
 impl<P: PublicId> Peer<P> {
@@ -38,6 +43,8 @@ impl<P: PublicId> Peer<P> {
             last_gossiped_event: None,
             #[cfg(feature = "malice-detection")]
             accomplice_event_checkpoint: None,
+            #[cfg(feature = "malice-detection")]
+            consecutive_stale_messages: 0,
         }
     }
 
@@ -77,6 +84,12 @@ impl<P: PublicId> Peer<P> {
         self.events.by_index(index)
     }
 
+    /// Returns the number of indices-by-creator at which we know of more than one event by this
+    /// peer.
+    pub fn num_forks(&self) -> usize {
+        self.events.num_forks()
+    }
+
     pub fn removal_event(&self) -> Option<EventIndex> {
         match self.presence {
             Presence::Present(_) => None,
@@ -84,8 +97,12 @@ impl<P: PublicId> Peer<P> {
         }
     }
 
-    pub(super) fn add_event(&mut self, index_by_creator: usize, event_index: EventIndex) {
-        self.events.add(index_by_creator, event_index);
+    pub(super) fn add_event(
+        &mut self,
+        index_by_creator: usize,
+        event_index: EventIndex,
+    ) -> Result<(), Error> {
+        self.events.add(index_by_creator, event_index)
     }
 
     #[cfg(any(all(test, feature = "mock"), feature = "testing"))]
@@ -94,14 +111,14 @@ impl<P: PublicId> Peer<P> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Presence {
     Present(PeerState),
     // Contains the index of the event at which we reached the consensus on the removal.
     Removed(EventIndex),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(super) struct Events(Vec<Slot>);
 
 impl Events {
@@ -109,17 +126,19 @@ impl Events {
         Events(Vec::new())
     }
 
-    fn add(&mut self, index_by_creator: usize, event_index: EventIndex) {
+    fn add(&mut self, index_by_creator: usize, event_index: EventIndex) -> Result<(), Error> {
         if let Some(slot) = self.0.get_mut(index_by_creator) {
             slot.add(event_index);
-            return;
+            return Ok(());
         }
 
         if index_by_creator != self.0.len() {
-            log_or_panic!("Peer events must be added sequentially");
+            error!("Peer events must be added sequentially");
+            return Err(Error::Logic);
         }
 
-        self.0.push(Slot::new(event_index))
+        self.0.push(Slot::new(event_index));
+        Ok(())
     }
 
     #[cfg(any(all(test, feature = "mock"), feature = "testing"))]
@@ -150,6 +169,10 @@ impl Events {
             })
     }
 
+    fn num_forks(&self) -> usize {
+        self.0.iter().filter(|slot| !slot.rest.is_empty()).count()
+    }
+
     fn by_index<'a>(&'a self, index_by_creator: usize) -> impl Iterator<Item = EventIndex> + 'a {
         self.0
             .get(index_by_creator)
@@ -168,13 +191,14 @@ where
     {
         let mut events = Self::new();
         for event in iter {
-            events.add(event.index_by_creator(), event.event_index());
+            let _ = events.add(event.index_by_creator(), event.event_index());
         }
 
         events
     }
 }
 
+#[derive(Clone)]
 struct Slot {
     first: EventIndex,
     rest: Vec<EventIndex>,
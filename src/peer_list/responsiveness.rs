@@ -0,0 +1,39 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+/// Statistics about how responsive a peer is to gossip requests, as recorded in our gossip graph.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct ResponsivenessStats {
+    num_responses: usize,
+    total_response_gap: usize,
+}
+
+impl ResponsivenessStats {
+    // Records a response which the requester handled `gap` of its own events after sending the
+    // request.
+    pub(crate) fn add_response(&mut self, gap: usize) {
+        self.num_responses += 1;
+        self.total_response_gap += gap;
+    }
+
+    /// Returns the number of gossip requests the peer has responded to.
+    pub fn num_responses(&self) -> usize {
+        self.num_responses
+    }
+
+    /// Returns the average number of events the requesters created between sending a request to
+    /// the peer and handling its response, or `None` if the peer hasn't responded to any request
+    /// yet. Higher values indicate a slower peer.
+    pub fn average_response_gap(&self) -> Option<f64> {
+        if self.num_responses == 0 {
+            None
+        } else {
+            Some(self.total_response_gap as f64 / self.num_responses as f64)
+        }
+    }
+}
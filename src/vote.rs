@@ -28,10 +28,31 @@ pub struct Vote<T: NetworkEvent, P: PublicId> {
 impl<T: NetworkEvent, P: PublicId> Vote<T, P> {
     /// Creates a `Vote` for `payload`.
     pub fn new<S: SecretId<PublicId = P>>(secret_id: &S, payload: Observation<T, P>) -> Self {
-        let signature = secret_id.sign_detached(&serialise(&payload));
+        let signature = secret_id.sign_detached(&Self::signed_data(&payload));
         Self { payload, signature }
     }
 
+    /// Reconstructs the `Vote` for `payload` which `proof` was created from, e.g. to check one of
+    /// the proofs of a `Block` without a `Parsec` instance.  The result is only valid if
+    /// `proof.signature()` was actually made over `payload` (see `verify`).
+    pub fn from_proof(payload: Observation<T, P>, proof: &Proof<P>) -> Self {
+        Self {
+            payload,
+            signature: proof.signature().clone(),
+        }
+    }
+
+    /// Returns the data a `Vote` for `payload` signs over. That is the serialised `payload`, unless
+    /// it is identified by the canonical hashes of its opaque payloads (see
+    /// `NetworkEvent::canonical_hash`), in which case it's derived from those. This way, the proofs
+    /// of votes for logically equal payloads verify against any of them.
+    pub fn signed_data(payload: &Observation<T, P>) -> Vec<u8> {
+        match ObservationHash::canonical(payload) {
+            Some(hash) => serialise(&hash),
+            None => serialise(payload),
+        }
+    }
+
     /// Returns the payload being voted for.
     pub fn payload(&self) -> &Observation<T, P> {
         &self.payload
@@ -44,7 +65,13 @@ impl<T: NetworkEvent, P: PublicId> Vote<T, P> {
 
     /// Validates this `Vote`'s signature and payload against the given public ID.
     pub fn is_valid(&self, public_id: &P) -> bool {
-        public_id.verify_signature(&self.signature, &serialise(&self.payload))
+        self.verify(public_id)
+    }
+
+    /// Verifies that this `Vote`'s signature was made by `public_id` over the payload.  Returns
+    /// `true` if valid.
+    pub fn verify(&self, public_id: &P) -> bool {
+        public_id.verify_signature(&self.signature, &Self::signed_data(&self.payload))
     }
 
     /// Creates a `Proof` from this `Vote`.  Returns `Err` if this `Vote` is not valid (i.e. if
@@ -115,3 +142,98 @@ impl<P: PublicId> Debug for VoteKey<P> {
         write!(f, "{:?}", self.payload_key)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::Block,
+        hash::Hash,
+        mock::{PeerId, Transaction},
+    };
+    use std::{cmp::Ordering, collections::BTreeMap};
+
+    #[test]
+    fn block_proofs_are_verifiable_as_votes() {
+        let alice = PeerId::new("Alice");
+        let bob = PeerId::new("Bob");
+        let payload = Observation::OpaquePayload(Transaction::new("ABCD"));
+
+        let votes: BTreeMap<_, _> = vec![&alice, &bob]
+            .into_iter()
+            .map(|peer_id| (peer_id.clone(), Vote::new(peer_id, payload.clone())))
+            .collect();
+        let block = unwrap!(Block::new(&votes));
+
+        for proof in block.proofs() {
+            let vote = Vote::from_proof(block.payload().clone(), proof);
+            assert!(vote.verify(proof.public_id()));
+            assert!(proof.is_valid(&Vote::signed_data(block.payload())));
+        }
+
+        let other_payload = Observation::OpaquePayload(Transaction::new("EFGH"));
+        let proof = unwrap!(block.proofs().iter().next());
+        assert!(!Vote::from_proof(other_payload, proof).verify(proof.public_id()));
+    }
+
+    #[test]
+    fn block_proofs_with_canonically_equal_payloads_are_verifiable() {
+        // Name identified case-insensitively.
+        #[derive(Clone, Serialize, Deserialize, Debug)]
+        struct Name(String);
+
+        impl PartialEq for Name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for Name {}
+
+        impl PartialOrd for Name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.to_lowercase().cmp(&other.0.to_lowercase())
+            }
+        }
+
+        impl NetworkEvent for Name {
+            fn canonical_hash(&self) -> Option<Hash> {
+                Some(Hash::from(self.0.to_lowercase().as_bytes()))
+            }
+        }
+
+        let alice = PeerId::new("Alice");
+        let bob = PeerId::new("Bob");
+        let carol = PeerId::new("Carol");
+        let votes: BTreeMap<_, _> = vec![(&alice, "Dave"), (&bob, "DAVE"), (&carol, "Dave")]
+            .into_iter()
+            .map(|(peer_id, name)| {
+                let payload = Observation::OpaquePayload(Name(name.into()));
+                (peer_id.clone(), Vote::new(peer_id, payload))
+            })
+            .collect();
+        let block = unwrap!(Block::new(&votes));
+
+        // The payload is the one with the smallest serialised form, regardless of who voted for it.
+        match block.payload() {
+            Observation::OpaquePayload(Name(name)) => assert_eq!(name, "DAVE"),
+            payload => panic!("Unexpected payload {:?}", payload),
+        }
+
+        // Bob's vote is for a different serialisation of the payload, but his signature still
+        // verifies against the block's payload.
+        assert!(block.is_signed_by(&alice));
+        assert!(block.is_signed_by(&bob));
+        assert!(block.is_signed_by(&carol));
+        for proof in block.proofs() {
+            let vote = Vote::from_proof(block.payload().clone(), proof);
+            assert!(vote.verify(proof.public_id()));
+        }
+    }
+}
@@ -229,6 +229,22 @@ fn add_many_peers() {
     unwrap!(env.execute_schedule(schedule));
 }
 
+#[test]
+fn add_peers_knowing_partial_section() {
+    let mut env = Environment::new(SEED);
+    let options = ScheduleOptions {
+        genesis_size: 3,
+        peers_to_add: 4,
+        opaque_to_add: 2,
+        joiner_contacts: Some(1),
+        ..Default::default()
+    };
+
+    let schedule = Schedule::new(&mut env, &options);
+
+    unwrap!(env.execute_schedule(schedule));
+}
+
 #[test]
 fn add_few_peers_and_vote() {
     use parsec::dev_utils::ObservationEvent::*;
@@ -254,6 +270,52 @@ fn add_few_peers_and_vote() {
     unwrap!(env.execute_schedule(schedule));
 }
 
+#[test]
+fn simple_majority_threshold() {
+    let num_peers = 7;
+    let mut env = Environment::with_consensus_mode(
+        SEED,
+        ConsensusMode::Threshold {
+            numerator: 1,
+            denominator: 2,
+        },
+    );
+    // 4 out of 7 voters is more than half, but not a supermajority.
+    let options = ScheduleOptions {
+        genesis_size: num_peers,
+        opaque_to_add: 5,
+        opaque_voters: Sampling::Constant(4),
+        ..Default::default()
+    };
+
+    let schedule = Schedule::new(&mut env, &options);
+
+    let result = env.execute_schedule(schedule);
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn equally_voted_payloads() {
+    use parsec::dev_utils::ObservationEvent::*;
+
+    let mut env = Environment::new(SEED);
+    // All the payloads are voted for by every peer at the same time, so they end up with equal
+    // vote counts and every node still has to agree on their order.
+    let obs_schedule = ObservationSchedule {
+        genesis: Genesis::new(NAMES.iter().take(4).cloned().map(PeerId::new).collect()),
+        schedule: vec![
+            (50, Opaque(Transaction::new("one"))),
+            (50, Opaque(Transaction::new("two"))),
+            (50, Opaque(Transaction::new("three"))),
+        ],
+    };
+
+    let options = ScheduleOptions::default();
+    let schedule = Schedule::from_observation_schedule(&mut env, &options, obs_schedule);
+
+    unwrap!(env.execute_schedule(schedule));
+}
+
 // Run DKG with the 4 voters in genesis
 #[test]
 fn run_dkg() {